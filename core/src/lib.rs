@@ -0,0 +1,664 @@
+//! Headless state logic for the texthooker: the line store, undo history,
+//! id allocation, sync merging, and the ingest filters, free of any DOM or
+//! WASM dependency so it can be unit-tested natively and reused outside the
+//! browser frontend.
+
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+/// The lines of hooked text, keyed by id in insertion order.
+pub type LineMap = IndexMap<usize, Line>;
+
+/// A single line of hooked text.
+///
+/// `version` is bumped on every edit so views keyed on it re-render, and is
+/// persisted so sync can resolve conflicts per line. `added_at` is the
+/// arrival time in milliseconds since the epoch; manually created lines have
+/// none.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Line {
+    pub text: String,
+    #[serde(default)]
+    pub version: usize,
+    #[serde(default)]
+    pub added_at: Option<f64>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// A locked line refuses edits and removal until unlocked, protecting
+    /// curated text from misclicks.
+    #[serde(default)]
+    pub locked: bool,
+    /// The speaker name split off the front of the line, when marker
+    /// stripping recognized one.
+    #[serde(default)]
+    pub speaker: Option<String>,
+}
+
+impl Line {
+    pub fn new(text: String, added_at: Option<f64>) -> Self {
+        Self {
+            text,
+            version: 0,
+            added_at,
+            tags: Vec::new(),
+            locked: false,
+            speaker: None,
+        }
+    }
+}
+
+/// The id to hand out for the next inserted line: one past the largest id
+/// ever used, so removed ids are never reused within a log.
+pub fn next_id(lines: &LineMap) -> usize {
+    lines.keys().max().map_or(0, |id| id + 1)
+}
+
+/// A reversible operation on the line map. Applying an operation returns
+/// its inverse, so undoing and redoing share one code path and new kinds of
+/// operation only have to be written once.
+#[derive(Clone, Debug)]
+pub enum Operation {
+    /// Insert `line` as `id` at `index`.
+    Insert { id: usize, index: usize, line: Line },
+    /// Remove the line with `id`.
+    Remove { id: usize },
+    /// Set the text of `id` to `text`, bumping its version.
+    SetText { id: usize, text: String },
+    /// Replace the whole map with `lines`.
+    Replace { lines: LineMap },
+    /// Several operations applied in order as a single undo step.
+    Batch { operations: Vec<Operation> },
+}
+
+impl Operation {
+    /// Applies the operation to `lines` and returns its inverse.
+    ///
+    /// Returns `None` if the map doesn't match the state the operation
+    /// expects (missing id, out-of-range index); in debug builds that
+    /// asserts instead, since it means the history has drifted from the
+    /// state it describes.
+    #[must_use]
+    pub fn apply(self, lines: &mut LineMap) -> Option<Operation> {
+        match self {
+            Self::Insert { id, index, line } => {
+                debug_assert!(index <= lines.len(), "insert index out of range");
+                debug_assert!(!lines.contains_key(&id), "inserted id already present");
+                if index > lines.len() || lines.contains_key(&id) {
+                    return None;
+                }
+                lines.shift_insert(index, id, line);
+                Some(Self::Remove { id })
+            }
+            Self::Remove { id } => {
+                let removed = lines.shift_remove_full(&id);
+                debug_assert!(removed.is_some(), "removed line does not exist");
+                let (index, id, line) = removed?;
+                Some(Self::Insert { id, index, line })
+            }
+            Self::SetText { id, text } => {
+                let line = lines.get_mut(&id);
+                debug_assert!(line.is_some(), "edited line does not exist");
+                let line = line?;
+                line.version += 1;
+                let text = std::mem::replace(&mut line.text, text);
+                Some(Self::SetText { id, text })
+            }
+            Self::Replace { lines: new } => {
+                let old = std::mem::replace(lines, new);
+                Some(Self::Replace { lines: old })
+            }
+            Self::Batch { operations } => {
+                let mut inverses: Vec<Operation> = operations
+                    .into_iter()
+                    .map(|operation| operation.apply(lines))
+                    .collect::<Option<_>>()?;
+                inverses.reverse();
+                Some(Self::Batch {
+                    operations: inverses,
+                })
+            }
+        }
+    }
+}
+
+/// Consecutive edits of one line this close together undo as a single
+/// step, instead of one step per focus cycle.
+pub const EDIT_COALESCE_WINDOW_MS: f64 = 5_000.0;
+
+/// Undo and redo histories, each a stack of inverse operations. Recording
+/// any fresh operation clears the redo side.
+#[derive(Clone, Debug, Default)]
+pub struct UndoStack {
+    undo: Vec<Operation>,
+    redo: Vec<Operation>,
+    /// The line and time of the last recorded edit, for coalescing; any
+    /// other recorded operation or an undo breaks the run.
+    last_edit: Option<(usize, f64)>,
+}
+
+impl UndoStack {
+    /// Records the inverse of a freshly performed operation, invalidating
+    /// the redo history.
+    pub fn push(&mut self, inverse: Operation) {
+        self.last_edit = None;
+        self.undo.push(inverse);
+        self.redo.clear();
+    }
+
+    /// Records an edit of `id` whose text was `old_text`, coalescing it
+    /// into the previous entry when that is an edit of the same line within
+    /// [`EDIT_COALESCE_WINDOW_MS`]. The entry already on the stack restores
+    /// the oldest text, so coalescing only has to drop the new inverse.
+    pub fn push_edit(&mut self, id: usize, old_text: String, now_ms: f64) {
+        let run_continues = self
+            .last_edit
+            .is_some_and(|(last_id, at)| last_id == id && now_ms - at < EDIT_COALESCE_WINDOW_MS)
+            && matches!(self.undo.last(), Some(Operation::SetText { id: top, .. }) if *top == id);
+        if !run_continues {
+            self.push(Operation::SetText { id, text: old_text });
+        } else {
+            // Still a fresh operation: the redo history is stale either way.
+            self.redo.clear();
+        }
+        self.last_edit = Some((id, now_ms));
+    }
+
+    /// The number of undoable operations, used to tell whether a given
+    /// operation is still on top of the stack.
+    pub fn undo_depth(&self) -> usize {
+        self.undo.len()
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Reverses the most recent operation against `lines`, making it
+    /// redoable. Returns whether anything was undone.
+    pub fn undo(&mut self, lines: &mut LineMap) -> bool {
+        self.last_edit = None;
+        let Some(operation) = self.undo.pop() else {
+            return false;
+        };
+        let Some(inverse) = operation.apply(lines) else {
+            return false;
+        };
+        self.redo.push(inverse);
+        true
+    }
+
+    /// Re-applies the most recently undone operation against `lines`.
+    /// Returns whether anything was redone.
+    pub fn redo(&mut self, lines: &mut LineMap) -> bool {
+        self.last_edit = None;
+        let Some(operation) = self.redo.pop() else {
+            return false;
+        };
+        let Some(inverse) = operation.apply(lines) else {
+            return false;
+        };
+        self.undo.push(inverse);
+        true
+    }
+}
+
+/// Merges a remote line set into the local one, keeping whichever copy of
+/// each line has the higher edit version; ties keep the local copy.
+pub fn merge_lines(local: &mut LineMap, remote: LineMap) {
+    for (id, line) in remote {
+        match local.get_mut(&id) {
+            Some(existing) if existing.version >= line.version => {}
+            Some(existing) => *existing = line,
+            None => {
+                local.insert(id, line);
+            }
+        }
+    }
+}
+
+/// How long a selection keeps suppressing short re-emitted lines, for the
+/// recent-lookup filter.
+pub const LOOKUP_FILTER_WINDOW_MS: f64 = 10_000.0;
+
+/// Lines longer than this are never treated as lookup echoes; real hooked
+/// sentences are rarely this short.
+pub const LOOKUP_FILTER_MAX_CHARS: usize = 12;
+
+/// Whether `text` looks like a dictionary-lookup echo: short, and contained
+/// in a selection recorded within the filter window. `recent` pairs a
+/// timestamp in epoch milliseconds with the selection made then.
+pub fn is_lookup_echo(text: &str, now_ms: f64, recent: &[(f64, String)]) -> bool {
+    text.chars().count() <= LOOKUP_FILTER_MAX_CHARS
+        && recent.iter().any(|(at, selected)| {
+            now_ms - at < LOOKUP_FILTER_WINDOW_MS && selected.contains(text)
+        })
+}
+
+fn is_kanji(c: char) -> bool {
+    matches!(c, '\u{4E00}'..='\u{9FFF}' | '\u{3400}'..='\u{4DBF}' | '々')
+}
+
+fn is_kana(c: char) -> bool {
+    matches!(c, '\u{3040}'..='\u{309F}' | '\u{30A0}'..='\u{30FF}')
+}
+
+/// The script the language filter expects incoming lines to contain.
+/// Hooked engines interleave the real text with ASCII debug strings and
+/// status messages, which contain none of it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TargetScript {
+    #[default]
+    Japanese,
+    Chinese,
+    Korean,
+}
+
+impl TargetScript {
+    fn contains_char(self, c: char) -> bool {
+        match self {
+            Self::Japanese => {
+                is_kana(c)
+                    || is_kanji(c)
+                    || matches!(c, '\u{31F0}'..='\u{31FF}' | '\u{FF66}'..='\u{FF9D}')
+            }
+            Self::Chinese => is_kanji(c),
+            Self::Korean => {
+                matches!(c, '\u{AC00}'..='\u{D7A3}' | '\u{1100}'..='\u{11FF}' | '\u{3130}'..='\u{318F}')
+            }
+        }
+    }
+}
+
+/// Whether `text` contains at least one character of `script`, used to
+/// tell hooked dialogue from the engine chatter around it.
+pub fn contains_script(text: &str, script: TargetScript) -> bool {
+    text.chars().any(|c| script.contains_char(c))
+}
+
+/// Removes furigana written in parentheses directly after kanji
+/// (e.g. 漢字（かんじ） → 漢字), which many game scripts embed inline.
+///
+/// The heuristic only strips a parenthesized run that consists entirely of
+/// kana and directly follows a kanji, so bracketed asides survive.
+pub fn strip_parenthesized_furigana(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    let mut last_was_kanji = false;
+    while let Some(c) = chars.next() {
+        if last_was_kanji && matches!(c, '（' | '(') {
+            let close = if c == '（' { '）' } else { ')' };
+            let mut reading_len = 0_usize;
+            let mut matched = false;
+            for next in chars.clone() {
+                if next == close {
+                    matched = reading_len > 0;
+                    break;
+                }
+                if !is_kana(next) {
+                    break;
+                }
+                reading_len += 1;
+            }
+            if matched {
+                // Skip the reading plus the closing parenthesis.
+                for _ in 0..=reading_len {
+                    chars.next();
+                }
+                last_was_kanji = false;
+                continue;
+            }
+        }
+        last_was_kanji = is_kanji(c);
+        out.push(c);
+    }
+    out
+}
+
+/// Splits a paragraph into sentences on 。！？ (and the ASCII forms),
+/// keeping the terminator — and any closing brackets right after it — with
+/// the sentence. Terminators inside 「…」/『…』 never split, so quoted
+/// dialogue stays whole.
+pub fn split_sentences(text: &str) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut current = String::new();
+    let mut depth = 0_usize;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        current.push(c);
+        match c {
+            '「' | '『' => depth += 1,
+            '」' | '』' => depth = depth.saturating_sub(1),
+            '。' | '！' | '？' | '!' | '?' if depth == 0 => {
+                while let Some(&next) = chars.peek() {
+                    if matches!(next, '」' | '』' | '）' | ')') {
+                        current.push(next);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                let sentence = current.trim();
+                if !sentence.is_empty() {
+                    out.push(sentence.to_string());
+                }
+                current.clear();
+            }
+            _ => {}
+        }
+    }
+    let rest = current.trim();
+    if !rest.is_empty() {
+        out.push(rest.to_string());
+    }
+    out
+}
+
+/// Whether `text` closes a paragraph in a novel-style dump: blank lines
+/// are spacing markers, and otherwise the line must end in terminal
+/// punctuation (closing quotes after it don't count). Hard-wrapped lines
+/// ending mid-sentence continue into the next line.
+pub fn ends_paragraph(text: &str) -> bool {
+    if text.trim().is_empty() {
+        return true;
+    }
+    let stripped = text.trim_end().trim_end_matches(['」', '』', '）', ')', '"']);
+    matches!(stripped.chars().last(), Some('。' | '！' | '？' | '!' | '?' | '…'))
+}
+
+/// Whether `new` re-sends `old` with more text appended — the shape VN
+/// engines produce when they emit the whole textbox every character tick.
+pub fn strictly_extends(new: &str, old: &str) -> bool {
+    new.len() > old.len() && new.starts_with(old)
+}
+
+/// Bracket pairs recognized as speaker markers when the user hasn't
+/// configured their own, written as alternating open/close characters.
+pub const SPEAKER_BRACKETS_DEFAULT: &str = "【】「」";
+
+/// Splits a leading speaker marker written in one of the bracket `pairs`
+/// (alternating open/close characters, as in [`SPEAKER_BRACKETS_DEFAULT`])
+/// off the line, so `【晴】おはよう` yields `("晴", "おはよう")`.
+///
+/// Only a marker at the very start is split off, and only when text follows
+/// it, so a line that is nothing but a quote survives.
+pub fn split_speaker_marker(text: &str, pairs: &str) -> Option<(String, String)> {
+    let pairs: Vec<char> = pairs.chars().collect();
+    for pair in pairs.chunks(2) {
+        let &[open, close] = pair else {
+            continue;
+        };
+        let Some(rest) = text.strip_prefix(open) else {
+            continue;
+        };
+        let Some((speaker, after)) = rest.split_once(close) else {
+            continue;
+        };
+        let after = after.trim_start();
+        if !after.is_empty() {
+            return Some((speaker.to_string(), after.to_string()));
+        }
+    }
+    None
+}
+
+/// [`split_speaker_marker`] for callers that only want the cleaned prose.
+pub fn strip_speaker_marker(text: &str, pairs: &str) -> String {
+    match split_speaker_marker(text, pairs) {
+        Some((_, rest)) => rest,
+        None => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(text: &str) -> Line {
+        Line::new(text.to_string(), None)
+    }
+
+    #[test]
+    fn next_id_skips_removed_ids() {
+        let mut lines = LineMap::new();
+        lines.insert(0, line("a"));
+        lines.insert(5, line("b"));
+        assert_eq!(next_id(&lines), 6);
+        lines.shift_remove(&5);
+        assert_eq!(next_id(&lines), 1);
+        assert_eq!(next_id(&LineMap::new()), 0);
+    }
+
+    #[test]
+    fn undo_redo_roundtrips_every_operation() {
+        let mut lines = LineMap::new();
+        let mut stack = UndoStack::default();
+
+        lines.insert(0, line("first"));
+        stack.push(Operation::Remove { id: 0 });
+        lines.insert(1, line("second"));
+        stack.push(Operation::Remove { id: 1 });
+
+        let old = std::mem::replace(&mut lines.get_mut(&0).unwrap().text, "edited".to_string());
+        stack.push(Operation::SetText { id: 0, text: old });
+
+        let (index, id, removed) = lines.shift_remove_full(&1).unwrap();
+        stack.push(Operation::Insert { id, index, line: removed });
+
+        let snapshot = lines.clone();
+        while stack.undo(&mut lines) {}
+        assert!(lines.is_empty());
+        while stack.redo(&mut lines) {}
+        assert_eq!(
+            lines.get(&0).map(|line| line.text.as_str()),
+            snapshot.get(&0).map(|line| line.text.as_str())
+        );
+        assert!(!lines.contains_key(&1));
+    }
+
+    #[test]
+    fn undo_remove_restores_position() {
+        let mut lines = LineMap::new();
+        lines.insert(0, line("a"));
+        lines.insert(1, line("b"));
+        lines.insert(2, line("c"));
+        let mut stack = UndoStack::default();
+        let (index, id, removed) = lines.shift_remove_full(&1).unwrap();
+        stack.push(Operation::Insert { id, index, line: removed });
+        stack.undo(&mut lines);
+        assert_eq!(lines.get_index_of(&1), Some(1));
+    }
+
+    #[test]
+    fn apply_returns_the_exact_inverse() {
+        let mut lines = LineMap::new();
+        lines.insert(0, line("a"));
+        let snapshot = lines.clone();
+        let inverse = Operation::SetText {
+            id: 0,
+            text: "b".to_string(),
+        }
+        .apply(&mut lines)
+        .unwrap();
+        assert_eq!(lines.get(&0).unwrap().text, "b");
+        inverse.apply(&mut lines).unwrap();
+        assert_eq!(lines.get(&0).unwrap().text, snapshot.get(&0).unwrap().text);
+    }
+
+    #[test]
+    fn batch_inverts_in_reverse_order() {
+        let mut lines = LineMap::new();
+        lines.insert(0, line("a"));
+        // Edit then remove the same line: inverting in forward order would
+        // try to edit a line that doesn't exist yet.
+        let batch = Operation::Batch {
+            operations: vec![
+                Operation::SetText {
+                    id: 0,
+                    text: "b".to_string(),
+                },
+                Operation::Remove { id: 0 },
+            ],
+        };
+        let inverse = batch.apply(&mut lines).unwrap();
+        assert!(lines.is_empty());
+        inverse.apply(&mut lines).unwrap();
+        assert_eq!(lines.get(&0).unwrap().text, "a");
+    }
+
+    #[test]
+    #[should_panic(expected = "edited line does not exist")]
+    fn apply_asserts_on_drifted_state() {
+        let mut lines = LineMap::new();
+        let _ = Operation::SetText {
+            id: 0,
+            text: String::new(),
+        }
+        .apply(&mut lines);
+    }
+
+    #[test]
+    fn fresh_push_clears_redo() {
+        let mut lines = LineMap::new();
+        let mut stack = UndoStack::default();
+        lines.insert(0, line("a"));
+        stack.push(Operation::Remove { id: 0 });
+        stack.undo(&mut lines);
+        assert!(stack.can_redo());
+        lines.insert(1, line("b"));
+        stack.push(Operation::Remove { id: 1 });
+        assert!(!stack.can_redo());
+    }
+
+    #[test]
+    fn merge_keeps_higher_version() {
+        let mut local = LineMap::new();
+        local.insert(0, Line { version: 2, ..line("local") });
+        local.insert(1, line("only local"));
+        let mut remote = LineMap::new();
+        remote.insert(0, Line { version: 1, ..line("remote stale") });
+        remote.insert(2, line("only remote"));
+        merge_lines(&mut local, remote);
+        assert_eq!(local.get(&0).unwrap().text, "local");
+        assert!(local.contains_key(&1));
+        assert_eq!(local.get(&2).unwrap().text, "only remote");
+
+        let mut remote = LineMap::new();
+        remote.insert(0, Line { version: 3, ..line("remote newer") });
+        merge_lines(&mut local, remote);
+        assert_eq!(local.get(&0).unwrap().text, "remote newer");
+    }
+
+    #[test]
+    fn lookup_echo_requires_recency_and_containment() {
+        let recent = vec![(1_000.0, "選択された単語".to_string())];
+        assert!(is_lookup_echo("単語", 2_000.0, &recent));
+        assert!(!is_lookup_echo("単語", 1_000.0 + LOOKUP_FILTER_WINDOW_MS, &recent));
+        assert!(!is_lookup_echo("別の言葉", 2_000.0, &recent));
+        let long = "こ".repeat(LOOKUP_FILTER_MAX_CHARS + 1);
+        assert!(!is_lookup_echo(&long, 2_000.0, &recent));
+    }
+
+    #[test]
+    fn strips_only_kana_readings_after_kanji() {
+        assert_eq!(strip_parenthesized_furigana("漢字（かんじ）"), "漢字");
+        assert_eq!(strip_parenthesized_furigana("漢字(かんじ)"), "漢字");
+        // Not after kanji: left alone.
+        assert_eq!(strip_parenthesized_furigana("あれ（それ）"), "あれ（それ）");
+        // Not kana inside: left alone.
+        assert_eq!(strip_parenthesized_furigana("漢字（注）"), "漢字（注）");
+        assert_eq!(strip_parenthesized_furigana("漢字（）"), "漢字（）");
+    }
+
+    #[test]
+    fn sentences_split_outside_quotes_only() {
+        assert_eq!(
+            split_sentences("これはペンだ。それもペンだ。"),
+            vec!["これはペンだ。", "それもペンだ。"]
+        );
+        // A terminator inside a quote doesn't split, and the closing
+        // bracket stays with its sentence.
+        assert_eq!(split_sentences("「行くの？」と聞いた。"), vec!["「行くの？」と聞いた。"]);
+        assert_eq!(split_sentences("まさか。「ええ？」"), vec!["まさか。", "「ええ？」"]);
+        // A trailing fragment without a terminator is kept.
+        assert_eq!(split_sentences("終わった。そして"), vec!["終わった。", "そして"]);
+        assert_eq!(split_sentences("　"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn paragraphs_close_on_punctuation_and_blanks() {
+        assert!(ends_paragraph("そう言った。"));
+        assert!(ends_paragraph("「行くの？」"));
+        assert!(ends_paragraph("まさか…"));
+        assert!(ends_paragraph("　"));
+        // Hard-wrapped mid-sentence: continues.
+        assert!(!ends_paragraph("そして彼は、"));
+        assert!(!ends_paragraph("どこまでも続く道"));
+    }
+
+    #[test]
+    fn extension_must_be_a_strict_prefix_growth() {
+        assert!(strictly_extends("こんにちは", "こんに"));
+        assert!(!strictly_extends("こんに", "こんにちは"));
+        assert!(!strictly_extends("こんにちは", "こんにちは"));
+        assert!(!strictly_extends("さような", "こんに"));
+    }
+
+    #[test]
+    fn strips_only_leading_speaker_markers() {
+        let pairs = SPEAKER_BRACKETS_DEFAULT;
+        assert_eq!(strip_speaker_marker("【晴】おはよう", pairs), "おはよう");
+        assert_eq!(strip_speaker_marker("「晴」 おはよう", pairs), "おはよう");
+        // A marker mid-line is dialogue, not a speaker.
+        assert_eq!(strip_speaker_marker("彼は【晴】と言った", pairs), "彼は【晴】と言った");
+        // A line that is nothing but a quote survives.
+        assert_eq!(strip_speaker_marker("「おはよう」", pairs), "「おはよう」");
+        // Unclosed marker: left alone.
+        assert_eq!(strip_speaker_marker("【晴 おはよう", pairs), "【晴 おはよう");
+        // Custom pairs.
+        assert_eq!(strip_speaker_marker("[晴] おはよう", "[]"), "おはよう");
+        // The split form also hands back the name.
+        assert_eq!(
+            split_speaker_marker("【晴】おはよう", pairs),
+            Some(("晴".to_string(), "おはよう".to_string()))
+        );
+    }
+
+    #[test]
+    fn consecutive_edits_of_one_line_coalesce() {
+        let mut lines = LineMap::new();
+        lines.insert(0, line("a"));
+        let mut stack = UndoStack::default();
+
+        lines.get_mut(&0).expect("line exists").text = "ab".to_string();
+        stack.push_edit(0, "a".to_string(), 0.0);
+        lines.get_mut(&0).expect("line exists").text = "abc".to_string();
+        stack.push_edit(0, "ab".to_string(), 1_000.0);
+        // One step back restores the text from before the whole run.
+        assert_eq!(stack.undo_depth(), 1);
+        assert!(stack.undo(&mut lines));
+        assert_eq!(lines[&0].text, "a");
+
+        // Outside the window, or on another line, edits stay separate; an
+        // undo also breaks the run.
+        stack.push_edit(0, "a".to_string(), 10_000.0);
+        stack.push_edit(0, "b".to_string(), 10_000.0 + EDIT_COALESCE_WINDOW_MS);
+        stack.push_edit(1, "x".to_string(), 10_000.0 + EDIT_COALESCE_WINDOW_MS);
+        assert_eq!(stack.undo_depth(), 3);
+    }
+
+    #[test]
+    fn script_detection_tells_dialogue_from_engine_chatter() {
+        assert!(contains_script("今日もいい天気だ", TargetScript::Japanese));
+        assert!(contains_script("ｶﾀｶﾅ", TargetScript::Japanese));
+        assert!(!contains_script("[DEBUG] scene_07 loaded (2.3ms)", TargetScript::Japanese));
+        assert!(contains_script("你好", TargetScript::Chinese));
+        // Kana alone isn't Chinese.
+        assert!(!contains_script("こんにちは", TargetScript::Chinese));
+        assert!(contains_script("안녕하세요", TargetScript::Korean));
+        assert!(!contains_script("annyeong", TargetScript::Korean));
+    }
+}
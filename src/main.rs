@@ -0,0 +1,5613 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::time::Duration;
+
+use base64::Engine as _;
+use indexmap::IndexMap;
+use leptos::{ev, html, *};
+use leptos_use::storage::use_local_storage;
+use leptos_use::utils::JsonCodec;
+use leptos_use::{use_event_listener, use_event_listener_with_options, UseEventListenerOptions};
+use serde::{Deserialize, Serialize};
+use texthooker_core::{
+    contains_script, ends_paragraph, is_lookup_echo, merge_lines, split_sentences,
+    split_speaker_marker, strictly_extends, strip_parenthesized_furigana, Line, LineMap, Operation,
+    TargetScript, UndoStack, LOOKUP_FILTER_WINDOW_MS, SPEAKER_BRACKETS_DEFAULT,
+};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Blob, KeyboardEvent, MutationObserver, MutationObserverInit, MutationRecord, Url};
+
+/// The reading font size in pixels, persisted separately from the lines.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct FontSize(u32);
+
+impl Default for FontSize {
+    fn default() -> Self {
+        Self(24)
+    }
+}
+
+/// Horizontal alignment applied to the line text.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+enum TextAlign {
+    #[default]
+    Left,
+    Center,
+    Right,
+    Justify,
+}
+
+impl TextAlign {
+    fn as_css(self) -> &'static str {
+        match self {
+            Self::Left => "left",
+            Self::Center => "center",
+            Self::Right => "right",
+            Self::Justify => "justify",
+        }
+    }
+}
+
+/// Base text direction applied to the line text, for right-to-left scripts.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+enum BaseDirection {
+    #[default]
+    Ltr,
+    Rtl,
+}
+
+impl BaseDirection {
+    fn as_css(self) -> &'static str {
+        match self {
+            Self::Ltr => "ltr",
+            Self::Rtl => "rtl",
+        }
+    }
+}
+
+/// How large the toolbar and per-line buttons render, independent of the
+/// reading font size.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+enum Density {
+    Compact,
+    #[default]
+    Normal,
+    Large,
+}
+
+impl Density {
+    /// The body class applied for this density; `Normal` is the stylesheet
+    /// default and needs none.
+    fn as_class(self) -> Option<&'static str> {
+        match self {
+            Self::Compact => Some("density_compact"),
+            Self::Normal => None,
+            Self::Large => Some("density_large"),
+        }
+    }
+}
+
+/// What the language filter does with an incoming line that contains no
+/// target-script characters.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+enum LanguageFilter {
+    #[default]
+    Off,
+    /// The line is dropped with a diagnostics entry.
+    Drop,
+    /// The line is kept but tagged and dimmed.
+    Flag,
+}
+
+/// The tag the language filter leaves on flagged lines; it rides along
+/// into the delimited export's tags column.
+const LANGUAGE_FLAG_TAG: &str = "non-target";
+
+/// When the working line map is flushed to localStorage. `Immediate`
+/// writes on every mutation as the app always did; the other two trade
+/// durability for less serialization churn during heavy editing.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+enum SavePolicy {
+    #[default]
+    Immediate,
+    Interval,
+    Manual,
+}
+
+/// How often interval autosave flushes unless the user configures
+/// otherwise.
+const AUTOSAVE_DEFAULT_INTERVAL_SECS: u32 = 10;
+
+/// How line text wraps. Japanese prose wraps anywhere, spaced scripts
+/// prefer keeping words whole, and code-like hook output reads best
+/// unwrapped behind a sideways scroll.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+enum WrapMode {
+    #[default]
+    Normal,
+    BreakAll,
+    KeepAll,
+    Scroll,
+}
+
+impl WrapMode {
+    /// The body class applied for this mode; `Normal` is the stylesheet
+    /// default and needs none.
+    fn as_class(self) -> Option<&'static str> {
+        match self {
+            Self::Normal => None,
+            Self::BreakAll => Some("wrap_break_all"),
+            Self::KeepAll => Some("wrap_keep_all"),
+            Self::Scroll => Some("wrap_scroll"),
+        }
+    }
+}
+
+/// How the most recently arrived line is marked.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+enum HighlightStyle {
+    #[default]
+    Off,
+    /// A short flash that fades out after arrival.
+    Flash,
+    /// A persistent tint until the next line arrives.
+    Tint,
+}
+
+/// One run of a line's text: either plain text or a ruby base with its
+/// reading.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum TextSegment {
+    Plain(String),
+    Ruby { base: String, reading: String },
+}
+
+/// Splits line text on the normalized `<ruby>base<rt>reading</rt></ruby>`
+/// notation produced at ingest. Anything malformed is passed through as plain
+/// text.
+fn parse_ruby(text: &str) -> Vec<TextSegment> {
+    let mut segments = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("<ruby>") {
+        let (plain, after) = rest.split_at(start);
+        let Some((base, after)) = after["<ruby>".len()..].split_once("<rt>") else {
+            break;
+        };
+        let Some((reading, after)) = after.split_once("</rt></ruby>") else {
+            break;
+        };
+        if !plain.is_empty() {
+            segments.push(TextSegment::Plain(plain.to_string()));
+        }
+        segments.push(TextSegment::Ruby {
+            base: base.to_string(),
+            reading: reading.to_string(),
+        });
+        rest = after;
+    }
+    if !rest.is_empty() {
+        segments.push(TextSegment::Plain(rest.to_string()));
+    }
+    segments
+}
+
+/// An action that can be bound to a keyboard shortcut.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+enum Action {
+    Undo,
+    Redo,
+    Clear,
+    ToggleZen,
+    AdvanceReadMarker,
+    FontSizeUp,
+    FontSizeDown,
+    CopyNewest,
+    TogglePause,
+    AddLine,
+    DeleteNewest,
+    SelectAllLines,
+}
+
+impl Action {
+    /// All bindable actions, in the order shown in settings.
+    const ALL: &'static [Self] = &[
+        Self::Undo,
+        Self::Redo,
+        Self::Clear,
+        Self::ToggleZen,
+        Self::AdvanceReadMarker,
+        Self::FontSizeUp,
+        Self::FontSizeDown,
+        Self::CopyNewest,
+        Self::TogglePause,
+        Self::AddLine,
+        Self::DeleteNewest,
+        Self::SelectAllLines,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Undo => "Undo",
+            Self::Redo => "Redo",
+            Self::Clear => "Clear lines",
+            Self::ToggleZen => "Toggle zen mode",
+            Self::AdvanceReadMarker => "Advance read marker",
+            Self::FontSizeUp => "Increase font size",
+            Self::FontSizeDown => "Decrease font size",
+            Self::CopyNewest => "Copy newest line",
+            Self::TogglePause => "Pause/resume capture",
+            Self::AddLine => "Add new line",
+            Self::DeleteNewest => "Delete newest line",
+            Self::SelectAllLines => "Select all line text",
+        }
+    }
+}
+
+#[wasm_bindgen]
+extern "C" {
+    // The async clipboard API is still unstable in web-sys, so bind
+    // `navigator.clipboard.writeText` directly.
+    #[wasm_bindgen(js_namespace = ["navigator", "clipboard"], js_name = writeText)]
+    fn clipboard_write_text(text: &str);
+
+    // Likewise for the Screen Wake Lock API; `catch` also covers browsers
+    // where `navigator.wakeLock` doesn't exist at all.
+    #[wasm_bindgen(js_namespace = ["navigator", "wakeLock"], js_name = request, catch)]
+    async fn wake_lock_request(kind: &str) -> Result<JsValue, JsValue>;
+}
+
+/// Releases a wake lock sentinel. Typed bindings for the sentinel are as
+/// unstable as the rest of the API, so the method is reached via `Reflect`.
+fn release_wake_lock(sentinel: &JsValue) {
+    if let Ok(release) = js_sys::Reflect::get(sentinel, &"release".into()) {
+        let release: js_sys::Function = release.unchecked_into();
+        let _ = release.call0(sentinel);
+    }
+}
+
+/// Keeps Tab cycling among the focusable elements of an open dialog.
+fn trap_focus(ev: &KeyboardEvent, container: &web_sys::Element) {
+    if ev.key() != "Tab" {
+        return;
+    }
+    let focusable = container
+        .query_selector_all("button, input, select")
+        .expect("valid selector");
+    let length = focusable.length();
+    if length == 0 {
+        ev.prevent_default();
+        return;
+    }
+    let element_at = |index: u32| {
+        focusable
+            .get(index)
+            .and_then(|node| node.dyn_into::<web_sys::HtmlElement>().ok())
+            .expect("focusable element")
+    };
+    let first = element_at(0);
+    let last = element_at(length - 1);
+    let active = document().active_element();
+    if ev.shift_key() && active.as_ref() == Some(AsRef::<web_sys::Element>::as_ref(&first)) {
+        ev.prevent_default();
+        last.focus().expect("focus should not fail");
+    } else if !ev.shift_key() && active.as_ref() == Some(AsRef::<web_sys::Element>::as_ref(&last)) {
+        ev.prevent_default();
+        first.focus().expect("focus should not fail");
+    }
+}
+
+/// Where AnkiConnect listens unless the user configures otherwise.
+#[cfg(feature = "anki")]
+const ANKI_CONNECT_DEFAULT_URL: &str = "http://127.0.0.1:8765";
+
+/// The note field the sentence is written to unless the user configures
+/// otherwise.
+#[cfg(feature = "anki")]
+const ANKI_DEFAULT_SENTENCE_FIELD: &str = "Sentence";
+
+/// A configured value, or the built-in default if the setting is empty.
+fn or_default(value: String, default: &str) -> String {
+    if value.is_empty() {
+        default.to_string()
+    } else {
+        value
+    }
+}
+
+/// Performs one AnkiConnect request and unwraps its `{result, error}`
+/// envelope.
+#[cfg(feature = "anki")]
+async fn anki_request(
+    url: &str,
+    action: &str,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, String> {
+    let body = serde_json::json!({ "action": action, "version": 6, "params": params });
+    let mut init = web_sys::RequestInit::new();
+    init.method("POST");
+    init.body(Some(&JsValue::from_str(&body.to_string())));
+    let request = web_sys::Request::new_with_str_and_init(url, &init)
+        .map_err(|_| "invalid AnkiConnect URL".to_string())?;
+    let response = JsFuture::from(window().fetch_with_request(&request))
+        .await
+        .map_err(|_| "AnkiConnect is unreachable".to_string())?;
+    let response: web_sys::Response = response.unchecked_into();
+    let text = JsFuture::from(response.text().expect("valid call"))
+        .await
+        .map_err(|_| "invalid response".to_string())?;
+    let value: serde_json::Value = text
+        .as_string()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .ok_or_else(|| "invalid response".to_string())?;
+    if let Some(error) = value.get("error").and_then(|error| error.as_str()) {
+        return Err(error.to_string());
+    }
+    Ok(value.get("result").cloned().unwrap_or(serde_json::Value::Null))
+}
+
+/// The deck new notes are created in unless the user configures otherwise.
+#[cfg(feature = "anki")]
+const ANKI_DEFAULT_DECK: &str = "Default";
+
+/// The note type new notes are created with unless the user configures
+/// otherwise.
+#[cfg(feature = "anki")]
+const ANKI_DEFAULT_NOTE_TYPE: &str = "Basic";
+
+/// What app data a mapped note field receives.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg(feature = "anki")]
+enum FieldSource {
+    #[default]
+    Sentence,
+    /// The sentence together with its surrounding lines.
+    Context,
+    /// The session title, once one is set.
+    Session,
+    Timestamp,
+}
+
+/// Whether a mapped field replaces the existing value or appends to it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg(feature = "anki")]
+enum FieldMode {
+    #[default]
+    Overwrite,
+    Append,
+}
+
+/// One user-configured note-field mapping.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg(feature = "anki")]
+struct FieldMapping {
+    field: String,
+    source: FieldSource,
+    mode: FieldMode,
+}
+
+/// The app data a note is built from; each [`FieldMapping`] picks one piece.
+#[cfg(feature = "anki")]
+struct AnkiPayload {
+    sentence: String,
+    context: String,
+    session: String,
+    timestamp: String,
+}
+
+#[cfg(feature = "anki")]
+impl AnkiPayload {
+    fn value(&self, source: FieldSource) -> &str {
+        match source {
+            FieldSource::Sentence => &self.sentence,
+            FieldSource::Context => &self.context,
+            FieldSource::Session => &self.session,
+            FieldSource::Timestamp => &self.timestamp,
+        }
+    }
+}
+
+/// Resolves the configured mappings against a payload. With no mappings
+/// configured, the whole sentence goes to the default sentence field.
+#[cfg(feature = "anki")]
+fn mapped_fields(
+    mappings: &[FieldMapping],
+    default_field: &str,
+    payload: &AnkiPayload,
+) -> Vec<(String, String, FieldMode)> {
+    let mappings: Vec<_> = mappings
+        .iter()
+        .filter(|mapping| !mapping.field.is_empty())
+        .collect();
+    if mappings.is_empty() {
+        return vec![(
+            default_field.to_string(),
+            payload.sentence.clone(),
+            FieldMode::Overwrite,
+        )];
+    }
+    mappings
+        .into_iter()
+        .map(|mapping| {
+            (
+                mapping.field.clone(),
+                payload.value(mapping.source).to_string(),
+                mapping.mode,
+            )
+        })
+        .collect()
+}
+
+/// Creates a new note from the mapped fields. Append mode is meaningless on
+/// a brand new note, so every field is written directly.
+#[cfg(feature = "anki")]
+async fn create_note(
+    url: &str,
+    deck: &str,
+    note_type: &str,
+    fields: Vec<(String, String, FieldMode)>,
+) -> Result<(), String> {
+    let fields: serde_json::Map<String, serde_json::Value> = fields
+        .into_iter()
+        .map(|(field, value, _)| (field, value.into()))
+        .collect();
+    anki_request(
+        url,
+        "addNote",
+        serde_json::json!({
+            "note": {
+                "deckName": deck,
+                "modelName": note_type,
+                "fields": fields,
+                "options": { "allowDuplicate": true },
+            }
+        }),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Writes the mapped fields into the most recently created note.
+#[cfg(feature = "anki")]
+async fn update_latest_note(
+    url: &str,
+    fields: Vec<(String, String, FieldMode)>,
+) -> Result<(), String> {
+    let notes = anki_request(url, "findNotes", serde_json::json!({ "query": "added:1" })).await?;
+    // Note ids are creation timestamps, so the largest is the newest.
+    let latest = notes
+        .as_array()
+        .and_then(|ids| ids.iter().filter_map(|id| id.as_u64()).max())
+        .ok_or_else(|| "no notes added today".to_string())?;
+    // Append mode needs the note's current values first.
+    let existing = if fields.iter().any(|(_, _, mode)| *mode == FieldMode::Append) {
+        anki_request(url, "notesInfo", serde_json::json!({ "notes": [latest] })).await?
+    } else {
+        serde_json::Value::Null
+    };
+    let fields: serde_json::Map<String, serde_json::Value> = fields
+        .into_iter()
+        .map(|(field, value, mode)| {
+            let value = match mode {
+                FieldMode::Overwrite => value,
+                FieldMode::Append => {
+                    let old = existing[0]["fields"][&field]["value"]
+                        .as_str()
+                        .unwrap_or_default();
+                    format!("{old}{value}")
+                }
+            };
+            (field, value.into())
+        })
+        .collect();
+    anki_request(
+        url,
+        "updateNoteFields",
+        serde_json::json!({ "note": { "id": latest, "fields": fields } }),
+    )
+    .await?;
+    Ok(())
+}
+
+/// The JPDB API root.
+#[cfg(feature = "anki")]
+const JPDB_API_URL: &str = "https://jpdb.io/api/v1";
+
+/// Performs one JPDB API request.
+#[cfg(feature = "anki")]
+async fn jpdb_request(
+    api_key: &str,
+    endpoint: &str,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, String> {
+    let mut init = web_sys::RequestInit::new();
+    init.method("POST");
+    init.body(Some(&JsValue::from_str(&params.to_string())));
+    let headers = web_sys::Headers::new().expect("valid call");
+    headers
+        .set("Authorization", &format!("Bearer {api_key}"))
+        .expect("valid call");
+    headers
+        .set("Content-Type", "application/json")
+        .expect("valid call");
+    init.headers(&headers);
+    let request =
+        web_sys::Request::new_with_str_and_init(&format!("{JPDB_API_URL}/{endpoint}"), &init)
+            .map_err(|_| "invalid request".to_string())?;
+    let response = JsFuture::from(window().fetch_with_request(&request))
+        .await
+        .map_err(|_| "JPDB is unreachable".to_string())?;
+    let response: web_sys::Response = response.unchecked_into();
+    let text = JsFuture::from(response.text().expect("valid call"))
+        .await
+        .map_err(|_| "invalid response".to_string())?;
+    let value: serde_json::Value = text
+        .as_string()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .ok_or_else(|| "invalid response".to_string())?;
+    if let Some(error) = value.get("error_message").and_then(|error| error.as_str()) {
+        return Err(error.to_string());
+    }
+    Ok(value)
+}
+
+/// Sets `sentence` as the example sentence of the JPDB vocab card for the
+/// first word of `word`.
+#[cfg(feature = "anki")]
+async fn jpdb_set_sentence(api_key: &str, word: &str, sentence: &str) -> Result<(), String> {
+    let parsed = jpdb_request(
+        api_key,
+        "parse",
+        serde_json::json!({
+            "text": [word],
+            "token_fields": ["vocabulary_index"],
+            "vocabulary_fields": ["vid", "sid"],
+        }),
+    )
+    .await?;
+    let vocabulary = &parsed["vocabulary"][0];
+    let (Some(vid), Some(sid)) = (vocabulary[0].as_u64(), vocabulary[1].as_u64()) else {
+        return Err("no vocabulary recognized".to_string());
+    };
+    jpdb_request(
+        api_key,
+        "set-card-sentence",
+        serde_json::json!({ "vid": vid, "sid": sid, "sentence": sentence }),
+    )
+    .await?;
+    Ok(())
+}
+
+/// The distance between the first two touches of a gesture, if there are two.
+fn touch_distance(ev: &web_sys::TouchEvent) -> Option<f64> {
+    let touches = ev.touches();
+    if touches.length() != 2 {
+        return None;
+    }
+    let a = touches.get(0)?;
+    let b = touches.get(1)?;
+    let dx = f64::from(a.client_x() - b.client_x());
+    let dy = f64::from(a.client_y() - b.client_y());
+    Some((dx * dx + dy * dy).sqrt())
+}
+
+/// Whether the page is running on macOS, where Cmd is the primary shortcut
+/// modifier instead of Ctrl.
+fn is_mac() -> bool {
+    window()
+        .navigator()
+        .platform()
+        .is_ok_and(|platform| platform.starts_with("Mac"))
+}
+
+/// The platform's primary shortcut modifier: Cmd on macOS, Ctrl elsewhere.
+fn primary_modifier(ev: &KeyboardEvent) -> bool {
+    if is_mac() {
+        ev.meta_key()
+    } else {
+        ev.ctrl_key()
+    }
+}
+
+/// A keyboard shortcut: modifiers plus a key value as reported by
+/// `KeyboardEvent::key`, stored lowercased. `ctrl` is the platform's primary
+/// modifier, so bindings recorded on one OS work on another.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct KeyBinding {
+    ctrl: bool,
+    alt: bool,
+    shift: bool,
+    key: String,
+}
+
+impl KeyBinding {
+    fn from_event(ev: &KeyboardEvent) -> Self {
+        Self {
+            ctrl: primary_modifier(ev),
+            alt: ev.alt_key(),
+            shift: ev.shift_key(),
+            key: ev.key().to_lowercase(),
+        }
+    }
+
+    fn matches(&self, ev: &KeyboardEvent) -> bool {
+        self.ctrl == primary_modifier(ev)
+            && self.alt == ev.alt_key()
+            && self.shift == ev.shift_key()
+            && self.key == ev.key().to_lowercase()
+    }
+}
+
+impl fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.ctrl {
+            write!(f, "{}", if is_mac() { "Cmd+" } else { "Ctrl+" })?;
+        }
+        if self.alt {
+            write!(f, "Alt+")?;
+        }
+        if self.shift {
+            write!(f, "Shift+")?;
+        }
+        match self.key.as_str() {
+            " " => write!(f, "Space"),
+            key => write!(f, "{key}"),
+        }
+    }
+}
+
+/// The user's shortcut assignments, persisted as a whole. Unbound actions are
+/// simply absent.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct KeyBindings(IndexMap<Action, KeyBinding>);
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        let ctrl = |key: &str| KeyBinding {
+            ctrl: true,
+            key: key.to_string(),
+            ..KeyBinding::default()
+        };
+        let alt = |key: &str| KeyBinding {
+            alt: true,
+            key: key.to_string(),
+            ..KeyBinding::default()
+        };
+        Self(IndexMap::from([
+            (Action::Undo, ctrl("z")),
+            (Action::Redo, ctrl("y")),
+            (Action::ToggleZen, alt("z")),
+            (Action::AdvanceReadMarker, alt("m")),
+            (Action::FontSizeUp, ctrl("=")),
+            (Action::FontSizeDown, ctrl("-")),
+            (Action::CopyNewest, alt("c")),
+            (Action::TogglePause, alt("p")),
+            (Action::AddLine, alt("n")),
+            (Action::DeleteNewest, alt("Backspace")),
+            (Action::SelectAllLines, ctrl("a")),
+        ]))
+    }
+}
+
+impl KeyBindings {
+    /// The action bound to this key event, if any.
+    fn action_for(&self, ev: &KeyboardEvent) -> Option<Action> {
+        self.0
+            .iter()
+            .find(|(_, binding)| binding.matches(ev))
+            .map(|(action, _)| *action)
+            .or_else(|| {
+                // Editor muscle memory: Ctrl+Shift+Z (Cmd+Shift+Z on macOS)
+                // redoes unless that chord is explicitly bound elsewhere.
+                (primary_modifier(ev)
+                    && ev.shift_key()
+                    && !ev.alt_key()
+                    && ev.key().to_lowercase() == "z")
+                    .then_some(Action::Redo)
+            })
+    }
+}
+
+/// Set while the shortcuts UI is waiting for a key press, so the global
+/// handler stays out of the way.
+#[derive(Clone, Copy)]
+struct RecordingAction(RwSignal<Option<Action>>);
+
+/// The current multi-select, shared with the export controls so "copy
+/// joined" can honour a selection.
+#[derive(Clone, Copy)]
+struct SelectedLines(RwSignal<HashSet<usize>>);
+
+/// The live working line map. Components read this instead of the
+/// storage-backed `"lines"` copy, which lags behind it under the deferred
+/// save policies.
+#[derive(Clone, Copy)]
+struct LiveLines(ReadSignal<LineMap>);
+
+/// The icons used by the toolbar and per-line buttons, rendered as inline SVG
+/// so nothing depends on an icon font being installed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Icon {
+    Delete,
+    Redo,
+    Undo,
+    Plus,
+    Pause,
+    Play,
+    Download,
+    Fullscreen,
+    FullscreenExit,
+    EyeOff,
+    Pencil,
+    Close,
+    Cog,
+    Bookmark,
+    #[cfg(feature = "anki")]
+    Send,
+    #[cfg(feature = "anki")]
+    PlusBox,
+    #[cfg(feature = "anki")]
+    Book,
+    Tag,
+    Lock,
+    ContentCopy,
+    ContentSave,
+    Share,
+    CloudUpload,
+    CloudDownload,
+    Console,
+}
+
+impl Icon {
+    /// Material Design Icons 24x24 path data.
+    fn path(self) -> &'static str {
+        match self {
+            Self::Delete => "M19,4H15.5L14.5,3H9.5L8.5,4H5V6H19M6,19A2,2 0 0,0 8,21H16A2,2 0 0,0 18,19V7H6V19Z",
+            Self::Redo => "M18.4,10.6C16.55,8.99 14.15,8 11.5,8C6.85,8 2.92,11.03 1.54,15.22L3.9,16C4.95,12.81 7.95,10.5 11.5,10.5C13.45,10.5 15.23,11.22 16.62,12.38L13,16H22V7L18.4,10.6Z",
+            Self::Undo => "M12.5,8C9.85,8 7.45,8.99 5.6,10.6L2,7V16H11L7.38,12.38C8.77,11.22 10.54,10.5 12.5,10.5C16.04,10.5 19.05,12.81 20.1,16L22.47,15.22C21.08,11.03 17.15,8 12.5,8Z",
+            Self::Plus => "M19,13H13V19H11V13H5V11H11V5H13V11H19V13Z",
+            Self::Pause => "M14,19H18V5H14M6,19H10V5H6V19Z",
+            Self::Play => "M8,5.14V19.14L19,12.14L8,5.14Z",
+            Self::Download => "M5,20H19V18H5M19,9H15V3H9V9H5L12,16L19,9Z",
+            Self::Fullscreen => "M5,5H10V7H7V10H5V5M14,5H19V10H17V7H14V5M17,14H19V19H14V17H17V14M10,17V19H5V14H7V17H10Z",
+            Self::FullscreenExit => "M14,14H19V16H16V19H14V14M5,14H10V19H8V16H5V14M8,5H10V10H5V8H8V5M19,8V10H14V5H16V8H19Z",
+            Self::EyeOff => "M11.83,9L15,12.16C15,12.11 15,12.05 15,12A3,3 0 0,0 12,9C11.94,9 11.89,9 11.83,9M7.53,9.8L9.08,11.35C9.03,11.56 9,11.77 9,12A3,3 0 0,0 12,15C12.22,15 12.44,14.97 12.65,14.92L14.2,16.47C13.53,16.8 12.79,17 12,17A5,5 0 0,1 7,12C7,11.21 7.2,10.47 7.53,9.8M2,4.27L4.28,6.55L4.73,7C3.08,8.3 1.78,10 1,12C2.73,16.39 7,19.5 12,19.5C13.55,19.5 15.03,19.2 16.38,18.66L16.81,19.08L19.73,22L21,20.73L3.27,3M12,7A5,5 0 0,1 17,12C17,12.64 16.87,13.26 16.64,13.82L19.57,16.75C21.07,15.5 22.27,13.86 23,12C21.27,7.61 17,4.5 12,4.5C10.6,4.5 9.26,4.75 8,5.2L10.17,7.35C10.74,7.13 11.35,7 12,7Z",
+            Self::Pencil => "M20.71,7.04C21.1,6.65 21.1,6 20.71,5.63L18.37,3.29C18,2.9 17.35,2.9 16.96,3.29L15.12,5.12L18.87,8.87M3,17.25V21H6.75L17.81,9.93L14.06,6.18L3,17.25Z",
+            Self::Close => "M19,6.41L17.59,5L12,10.59L6.41,5L5,6.41L10.59,12L5,17.59L6.41,19L12,13.41L17.59,19L19,17.59L13.41,12L19,6.41Z",
+            Self::Bookmark => "M17,3H7A2,2 0 0,0 5,5V21L12,18L19,21V5A2,2 0 0,0 17,3Z",
+            Self::Lock => "M12,17A2,2 0 0,0 14,15C14,13.89 13.1,13 12,13A2,2 0 0,0 10,15A2,2 0 0,0 12,17M18,8A2,2 0 0,1 20,10V20A2,2 0 0,1 18,22H6A2,2 0 0,1 4,20V10C4,8.89 4.9,8 6,8H7V6A5,5 0 0,1 12,1A5,5 0 0,1 17,6V8H18M12,3A3,3 0 0,0 9,6V8H15V6A3,3 0 0,0 12,3Z",
+            Self::ContentCopy => "M19,21H8V7H19M19,5H8A2,2 0 0,0 6,7V21A2,2 0 0,0 8,23H19A2,2 0 0,0 21,21V7A2,2 0 0,0 19,5M16,1H4A2,2 0 0,0 2,3V17H4V3H16V1Z",
+            Self::ContentSave => "M15,9H5V5H15M12,19A3,3 0 0,1 9,16A3,3 0 0,1 12,13A3,3 0 0,1 15,16A3,3 0 0,1 12,19M17,3H5C3.89,3 3,3.9 3,5V19A2,2 0 0,0 5,21H19A2,2 0 0,0 21,19V7L17,3Z",
+            #[cfg(feature = "anki")]
+            Self::Send => "M2,21L23,12L2,3V10L17,12L2,14V21Z",
+            #[cfg(feature = "anki")]
+            Self::PlusBox => "M17,13H13V17H11V13H7V11H11V7H13V11H17M19,3H5C3.89,3 3,3.89 3,5V19A2,2 0 0,0 5,21H19A2,2 0 0,0 21,19V5C21,3.89 20.1,3 19,3Z",
+            #[cfg(feature = "anki")]
+            Self::Book => "M18,2A2,2 0 0,1 20,4V20A2,2 0 0,1 18,22H6A2,2 0 0,1 4,20V4A2,2 0 0,1 6,2H18M18,4H13V12L10.5,9.75L8,12V4H6V20H18V4Z",
+            Self::CloudUpload => "M14,13V17H10V13H7L12,8L17,13M19.35,10.03C18.67,6.59 15.64,4 12,4C9.11,4 6.6,5.64 5.35,8.03C2.34,8.36 0,10.9 0,14A6,6 0 0,0 6,20H19A5,5 0 0,0 24,15C24,12.36 21.95,10.22 19.35,10.03Z",
+            Self::CloudDownload => "M17,13L12,18L7,13H10V9H14V13M19.35,10.03C18.67,6.59 15.64,4 12,4C9.11,4 6.6,5.64 5.35,8.03C2.34,8.36 0,10.9 0,14A6,6 0 0,0 6,20H19A5,5 0 0,0 24,15C24,12.36 21.95,10.22 19.35,10.03Z",
+            Self::Console => "M20,19V7H4V19H20M20,3A2,2 0 0,1 22,5V19A2,2 0 0,1 20,21H4A2,2 0 0,1 2,19V5C2,3.89 2.9,3 4,3H20M13,17V15H18V17H13M9.58,13L5.57,9H8.4L11.7,12.3C12.09,12.69 12.09,13.33 11.7,13.72L8.42,17H5.59L9.58,13Z",
+            Self::Share =>"M18,16.08C17.24,16.08 16.56,16.38 16.04,16.85L8.91,12.7C8.96,12.47 9,12.24 9,12C9,11.76 8.96,11.53 8.91,11.3L15.96,7.19C16.5,7.69 17.21,8 18,8A3,3 0 0,0 21,5A3,3 0 0,0 18,2A3,3 0 0,0 15,5C15,5.24 15.04,5.47 15.09,5.7L8.04,9.81C7.5,9.31 6.79,9 6,9A3,3 0 0,0 3,12A3,3 0 0,0 6,15C6.79,15 7.5,14.69 8.04,14.19L15.16,18.34C15.11,18.55 15.08,18.77 15.08,19C15.08,20.61 16.39,21.91 18,21.91C19.61,21.91 20.92,20.61 20.92,19A2.92,2.92 0 0,0 18,16.08Z",
+            Self::Tag =>"M5.5,7A1.5,1.5 0 0,1 4,5.5A1.5,1.5 0 0,1 5.5,4A1.5,1.5 0 0,1 7,5.5A1.5,1.5 0 0,1 5.5,7M21.41,11.58L12.41,2.58C12.05,2.22 11.55,2 11,2H4C2.89,2 2,2.89 2,4V11C2,11.55 2.22,12.05 2.59,12.41L11.58,21.41C11.95,21.78 12.45,22 13,22C13.55,22 14.05,21.78 14.41,21.41L21.41,14.41C21.78,14.05 22,13.55 22,13C22,12.45 21.77,11.94 21.41,11.58Z",
+            Self::Cog => "M12,15.5A3.5,3.5 0 0,1 8.5,12A3.5,3.5 0 0,1 12,8.5A3.5,3.5 0 0,1 15.5,12A3.5,3.5 0 0,1 12,15.5M19.43,12.97C19.47,12.65 19.5,12.33 19.5,12C19.5,11.67 19.47,11.34 19.43,11L21.54,9.37C21.73,9.22 21.78,8.95 21.66,8.73L19.66,5.27C19.54,5.05 19.27,4.96 19.05,5.05L16.56,6.05C16.04,5.66 15.5,5.32 14.87,5.07L14.5,2.42C14.46,2.18 14.25,2 14,2H10C9.75,2 9.54,2.18 9.5,2.42L9.13,5.07C8.5,5.32 7.96,5.66 7.44,6.05L4.95,5.05C4.73,4.96 4.46,5.05 4.34,5.27L2.34,8.73C2.21,8.95 2.27,9.22 2.46,9.37L4.57,11C4.53,11.34 4.5,11.67 4.5,12C4.5,12.33 4.53,12.65 4.57,12.97L2.46,14.63C2.27,14.78 2.21,15.05 2.34,15.27L4.34,18.73C4.46,18.95 4.73,19.03 4.95,18.95L7.44,17.94C7.96,18.34 8.5,18.68 9.13,18.93L9.5,21.58C9.54,21.82 9.75,22 10,22H14C14.25,22 14.46,21.82 14.5,21.58L14.87,18.93C15.5,18.67 16.04,18.34 16.56,17.94L19.05,18.95C19.27,19.03 19.54,18.95 19.66,18.73L21.66,15.27C21.78,15.05 21.73,14.78 21.54,14.63L19.43,12.97Z",
+        }
+    }
+}
+
+/// Renders an [`Icon`] as an inline SVG sized to the current font.
+#[component]
+fn IconView(#[prop(into)] icon: MaybeSignal<Icon>) -> impl IntoView {
+    view! {
+        <svg class="icon" viewBox="0 0 24 24" xmlns="http://www.w3.org/2000/svg">
+            <path fill="currentColor" d=move || icon.get().path()></path>
+        </svg>
+    }
+}
+
+/// How many diagnostics events are kept before the oldest are dropped.
+const DIAGNOSTICS_CAP: usize = 200;
+
+/// More lines than this within [`FLOOD_WINDOW_MS`] is faster than any
+/// human-facing source legitimately emits, and trips the flood guard.
+const FLOOD_THRESHOLD: usize = 20;
+
+/// The sliding window the flood guard counts arrivals over.
+const FLOOD_WINDOW_MS: f64 = 1000.0;
+
+/// One entry in the diagnostics panel: what the ingestion, websocket, and
+/// storage machinery did and when, so "why isn't my text showing up" can be
+/// answered without opening devtools.
+#[derive(Clone, Debug, PartialEq)]
+struct DiagEvent {
+    id: usize,
+    /// Epoch milliseconds.
+    at: f64,
+    message: String,
+}
+
+/// A transient notification shown after destructive or bulk actions.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Toast {
+    id: usize,
+    message: String,
+    /// Undo stack depth when the toast was created; the inline undo only
+    /// applies while its action is still on top of the stack.
+    undo_len: Option<usize>,
+}
+
+fn main() {
+    set_recovery_panic_hook();
+    if mirror_mode() {
+        mount_to_body(|| view! { <MirrorView/> });
+        return;
+    }
+    match shared_snapshot() {
+        Some(texts) => mount_to_body(move || view! { <SharedView texts/> }),
+        None => mount_to_body(|| {
+            view! {
+                <ErrorBoundary fallback=|_| view! { <RecoveryView/> }>
+                    <MainPage/>
+                </ErrorBoundary>
+            }
+        }),
+    }
+}
+
+/// Logs panics through `console_error_panic_hook` as before, but also swaps
+/// the dead page for a small recovery UI: without it a single failed
+/// `expect` aborts the WASM instance and strands the log behind a blank
+/// page with no way to get the data out.
+fn set_recovery_panic_hook() {
+    std::panic::set_hook(Box::new(|info| {
+        console_error_panic_hook::hook(info);
+        render_recovery_page(&info.to_string());
+    }));
+}
+
+/// Replaces the page body with the crash recovery UI. This runs mid-panic,
+/// so nothing here may panic again (that would abort before the page
+/// renders); failures are simply ignored.
+fn render_recovery_page(message: &str) {
+    let Some(body) = document().body() else {
+        return;
+    };
+    body.set_inner_html(&format!(
+        "<div class=\"recovery\">\
+         <div>The page hit an internal error and stopped.</div>\
+         <pre class=\"recovery_message\">{}</pre>\
+         <div class=\"modal_buttons\">\
+         <button id=\"recovery-export\">Export raw storage</button>\
+         <button id=\"recovery-reload\">Reload</button>\
+         </div>\
+         </div>",
+        escape_html(message),
+    ));
+    let hook = |id: &str, action: fn()| {
+        let Some(button) = document().get_element_by_id(id) else {
+            return;
+        };
+        let closure = Closure::<dyn Fn()>::new(action);
+        let _ = button.add_event_listener_with_callback("click", closure.as_ref().unchecked_ref());
+        // The page is past saving; leaking the handler is fine.
+        closure.forget();
+    };
+    hook("recovery-export", export_raw_storage);
+    hook("recovery-reload", || {
+        let _ = window().location().reload();
+    });
+}
+
+/// Dumps every local-storage key to a JSON file as-is, so the log survives
+/// even when the app can no longer parse it. Shares the panic path's
+/// no-panic rule.
+fn export_raw_storage() {
+    let Ok(Some(storage)) = window().local_storage() else {
+        return;
+    };
+    let mut dump = serde_json::Map::new();
+    for index in 0..storage.length().unwrap_or(0) {
+        let Ok(Some(key)) = storage.key(index) else {
+            continue;
+        };
+        let Ok(Some(value)) = storage.get_item(&key) else {
+            continue;
+        };
+        dump.insert(key, serde_json::Value::String(value));
+    }
+    let Ok(json) = serde_json::to_string_pretty(&serde_json::Value::Object(dump)) else {
+        return;
+    };
+    let Ok(blob) = Blob::new_with_str_sequence(&js_sys::Array::of1(&json.as_str().into())) else {
+        return;
+    };
+    let Ok(url) = Url::create_object_url_with_blob(&blob) else {
+        return;
+    };
+    let Some(anchor) = document()
+        .create_element("a")
+        .ok()
+        .and_then(|anchor| anchor.dyn_into::<web_sys::HtmlAnchorElement>().ok())
+    else {
+        return;
+    };
+    anchor.set_href(&url);
+    anchor.set_download("texthooker-storage.json");
+    anchor.click();
+    let _ = Url::revoke_object_url(&url);
+}
+
+/// Shown in place of the app when a render-time error escapes the error
+/// boundary. The reactive system is still alive here, unlike the panic
+/// path, so this stays a normal component.
+#[component]
+fn RecoveryView() -> impl IntoView {
+    view! {
+        <div class="recovery">
+            <div>"The page hit an internal error and stopped."</div>
+            <div class="modal_buttons">
+                <button on:click=|_| export_raw_storage()>"Export raw storage"</button>
+                <button on:click=|_| {
+                    let _ = window().location().reload();
+                }>"Reload"</button>
+            </div>
+        </div>
+    }
+}
+
+/// Encodes line texts into the compressed, URL-safe fragment used by
+/// shareable snapshot links.
+fn encode_snapshot(texts: &[String]) -> String {
+    let json = serde_json::to_string(texts).expect("valid json");
+    let compressed = miniz_oxide::deflate::compress_to_vec(json.as_bytes(), 6);
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(compressed)
+}
+
+/// Decodes a snapshot fragment back into line texts.
+fn decode_snapshot(encoded: &str) -> Option<Vec<String>> {
+    let compressed = base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(encoded)
+        .ok()?;
+    let json = miniz_oxide::inflate::decompress_to_vec(&compressed).ok()?;
+    serde_json::from_slice(&json).ok()
+}
+
+/// The channel the interactive tab re-broadcasts its full line set on, for
+/// `?mirror` tabs to follow.
+const MIRROR_CHANNEL: &str = "texthooker-mirror";
+
+/// Whether the page was opened with the `?mirror` query parameter.
+fn mirror_mode() -> bool {
+    let search = window().location().search().expect("valid call");
+    let params = web_sys::UrlSearchParams::new_with_str(&search).expect("valid call");
+    params.get("mirror").is_some()
+}
+
+/// The read-only presentation view behind `?mirror`: the same log in large
+/// type with no controls, for a tab facing a tutor or a stream. The initial
+/// state comes straight from the shared localStorage, and afterwards the
+/// interactive tab keeps it live over a BroadcastChannel.
+#[component]
+fn MirrorView() -> impl IntoView {
+    let (stored, _, _) = use_local_storage::<LineMap, JsonCodec>("lines");
+    let lines = create_rw_signal(stored.get_untracked());
+    if let Ok(channel) = web_sys::BroadcastChannel::new(MIRROR_CHANNEL) {
+        let on_message =
+            Closure::<dyn Fn(web_sys::MessageEvent)>::new(move |ev: web_sys::MessageEvent| {
+                let Some(frame) = ev.data().as_string() else {
+                    return;
+                };
+                let Ok(remote) = serde_json::from_str::<LineMap>(&frame) else {
+                    return;
+                };
+                lines.set(remote);
+            });
+        channel.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+        on_message.forget();
+    }
+    // Follow the newest line, as the presentation tab has no one scrolling
+    // it.
+    create_effect(move |_| {
+        lines.track();
+        let body = document().body().expect("body exists");
+        window().scroll_to_with_x_and_y(0.0, body.scroll_height() as f64);
+    });
+
+    view! {
+        <div id="mirror">
+            <For
+                each=move || lines.with(|lines| lines.keys().copied().collect::<Vec<_>>())
+                key=|id| *id
+                children=move |id| {
+                    let text = create_memo(move |_| {
+                        lines
+                            .with(|lines| {
+                                lines.get(&id).map(|line| line.text.clone()).unwrap_or_default()
+                            })
+                    });
+                    view! { <p class="mirror_line">{text}</p> }
+                }
+            />
+        </div>
+    }
+}
+
+/// Returns the shared lines if the page was opened through a snapshot link.
+fn shared_snapshot() -> Option<Vec<String>> {
+    let hash = window().location().hash().expect("valid call");
+    decode_snapshot(hash.strip_prefix("#share=")?)
+}
+
+/// Read-only rendering of a shared snapshot: just the lines, no controls.
+#[component]
+fn SharedView(texts: Vec<String>) -> impl IntoView {
+    view! {
+        <div class="shared_banner">"Shared snapshot (read-only)"</div>
+        <div id="lines">
+            {texts
+                .into_iter()
+                .map(|text| {
+                    view! {
+                        <div class="line_box">
+                            <span class="line_text">{text}</span>
+                        </div>
+                    }
+                })
+                .collect_view()}
+        </div>
+    }
+}
+
+/// Where an unreadable `lines` value is stashed before the codec's default
+/// can overwrite it.
+const CORRUPTED_LINES_KEY: &str = "lines-corrupted";
+
+/// Checks the raw `lines` storage value before the codec touches it. If it
+/// fails to deserialize (schema change, manual tampering, partial write),
+/// the raw value is copied to [`CORRUPTED_LINES_KEY`] and returned so the
+/// recovery dialog can offer it back; the codec would otherwise silently
+/// replace it with an empty log. A stash left over from an earlier session
+/// is returned again until the user discards it.
+fn stash_corrupted_lines() -> Option<String> {
+    let storage = window().local_storage().ok()??;
+    if let Ok(Some(stashed)) = storage.get_item(CORRUPTED_LINES_KEY) {
+        return Some(stashed);
+    }
+    let raw = storage.get_item("lines").ok()??;
+    if serde_json::from_str::<LineMap>(&raw).is_ok() {
+        return None;
+    }
+    storage.set_item(CORRUPTED_LINES_KEY, &raw).ok()?;
+    Some(raw)
+}
+
+#[component]
+fn MainPage() -> impl IntoView {
+    let corrupted_lines = store_value(stash_corrupted_lines());
+    let recovery_open = create_rw_signal(corrupted_lines.with_value(Option::is_some));
+    // Persistence is decoupled from mutation: the working map lives in a
+    // plain signal, and is flushed into the storage-backed copy according
+    // to the save policy below — so heavy editing doesn't serialize the
+    // whole log into localStorage on every keystroke.
+    let (stored_lines, set_stored_lines, _) = use_local_storage::<LineMap, JsonCodec>("lines");
+    let (lines, set_lines) = create_signal(stored_lines.get_untracked());
+    provide_context(LiveLines(lines));
+    let (save_policy, _, _) = use_local_storage::<SavePolicy, JsonCodec>("save-policy");
+    let (autosave_interval, _, _) = use_local_storage::<u32, JsonCodec>("autosave-interval");
+    let dirty = create_rw_signal(false);
+    let (font_size, set_font_size, _) = use_local_storage::<FontSize, JsonCodec>("font-size");
+    let (text_align, _, _) = use_local_storage::<TextAlign, JsonCodec>("text-align");
+    let (auto_hide_toolbar, _, _) = use_local_storage::<bool, JsonCodec>("auto-hide-toolbar");
+    let (line_numbers, _, _) = use_local_storage::<bool, JsonCodec>("line-numbers");
+    let (skip_clear_confirm, _, _) = use_local_storage::<bool, JsonCodec>("skip-clear-confirm");
+    let (strip_furigana, _, _) = use_local_storage::<bool, JsonCodec>("strip-furigana");
+    let (strip_speaker, _, _) = use_local_storage::<bool, JsonCodec>("strip-speaker");
+    let (speaker_brackets, _, _) = use_local_storage::<String, JsonCodec>("speaker-brackets");
+    let (merge_extensions, _, _) = use_local_storage::<bool, JsonCodec>("merge-extensions");
+    let (sentence_split, _, _) = use_local_storage::<bool, JsonCodec>("split-sentences");
+    let (language_filter, _, _) = use_local_storage::<LanguageFilter, JsonCodec>("language-filter");
+    let (target_script, _, _) = use_local_storage::<TargetScript, JsonCodec>("target-script");
+    let (scroll_lock_editing, _, _) = use_local_storage::<bool, JsonCodec>("scroll-lock-editing");
+    let (read_marker, set_read_marker, _) =
+        use_local_storage::<Option<usize>, JsonCodec>("read-marker");
+    let (direction, _, _) = use_local_storage::<BaseDirection, JsonCodec>("direction");
+
+    // Ids are never reused, so the next one is simply past the largest seen.
+    let next_id = store_value(lines.with_untracked(texthooker_core::next_id));
+    let alloc_id = move || {
+        let id = next_id.get_value();
+        next_id.set_value(id + 1);
+        id
+    };
+
+    let undo_stack = create_rw_signal(UndoStack::default());
+    // Which line is currently being edited, if any; most global shortcuts are
+    // suppressed while one is.
+    let focused_id = create_rw_signal(None::<usize>);
+    // Set by `add_focused_entry` so the freshly created `LineView` starts out
+    // in edit mode.
+    let pending_focus = create_rw_signal(None::<usize>);
+    // A session can carry a title, cover color, and notes naming what is
+    // being read, shown above the log and stamped into exports and stats
+    // pushes so saved logs stay attributable.
+    let (session_title, _, _) = use_local_storage::<String, JsonCodec>("session-title");
+    let (session_color, _, _) = use_local_storage::<String, JsonCodec>("session-color");
+    let (session_notes, _, _) = use_local_storage::<String, JsonCodec>("session-notes");
+
+    // An optional second workspace rendered beside the main log — a plain
+    // list for translation notes or a parallel session. It scrolls on its
+    // own, and ingestion can be routed into it instead of the main log.
+    let (split_view, _, _) = use_local_storage::<bool, JsonCodec>("split-view");
+    let (ingest_secondary, _, _) = use_local_storage::<bool, JsonCodec>("ingest-secondary");
+    let (secondary_lines, set_secondary_lines, _) =
+        use_local_storage::<LineMap, JsonCodec>("lines-secondary");
+    let add_secondary = move |texts: Vec<String>| {
+        set_secondary_lines.update(|lines| {
+            for text in texts {
+                let id = texthooker_core::next_id(lines);
+                lines.insert(id, Line::new(text, Some(js_sys::Date::now())));
+            }
+        });
+    };
+
+    // Novel-style dumps arrive hard-wrapped mid-sentence; paragraph view
+    // joins consecutive lines until terminal punctuation or a blank marker
+    // and renders the groups as plain blocks instead of one box per hooked
+    // line. Purely a rendering mode: the stored lines are untouched.
+    let (paragraph_view, _, _) = use_local_storage::<bool, JsonCodec>("paragraph-view");
+    let paragraphs = create_memo(move |_| {
+        lines.with(|lines| {
+            let mut groups: Vec<(usize, String)> = Vec::new();
+            let mut open = false;
+            for (&id, line) in lines {
+                if line.text.trim().is_empty() {
+                    open = false;
+                    continue;
+                }
+                if !open {
+                    groups.push((id, String::new()));
+                    open = true;
+                }
+                groups.last_mut().expect("group exists").1.push_str(line.text.trim());
+                if ends_paragraph(&line.text) {
+                    open = false;
+                }
+            }
+            groups
+        })
+    });
+
+    // Display-only speaker filter; the empty string means all speakers.
+    let (speaker_filter, _, _) = use_local_storage::<String, JsonCodec>("speaker-filter");
+
+    // Runs of identical consecutive lines (menu flicker, hook retransmits)
+    // can be collapsed to their first copy with a repeat badge. Only the
+    // view collapses: every copy stays in the map, so undo, exports, and
+    // the stats character counts are unaffected.
+    let (collapse_repeats, _, _) = use_local_storage::<bool, JsonCodec>("collapse-repeats");
+    let expanded_runs = create_rw_signal(HashSet::<usize>::new());
+    let toggle_expand = move |id: usize| {
+        expanded_runs.update(|expanded| {
+            if !expanded.remove(&id) {
+                expanded.insert(id);
+            }
+        });
+    };
+
+    // The whole-log read-only lock: editing, deleting, clearing, and
+    // undo/redo are refused while it's on, so a screen-shared or
+    // accidentally touched page can't damage the log. New lines still
+    // arrive. Persisted, so a reload mid-presentation stays locked.
+    let (read_only, set_read_only, _) = use_local_storage::<bool, JsonCodec>("read-only");
+
+    // The line most recently added by the hook, for the highlight setting.
+    let newest_id = create_rw_signal(None::<usize>);
+    let paused = create_rw_signal(false);
+    let (zen, set_zen, _) = use_local_storage::<bool, JsonCodec>("zen-mode");
+    let overlay = overlay_mode();
+
+    let (density, _, _) = use_local_storage::<Density, JsonCodec>("density");
+    create_effect(move |_| {
+        let class_list = document().body().expect("body exists").class_list();
+        class_list
+            .remove_2("density_compact", "density_large")
+            .expect("valid call");
+        if let Some(class) = density.get().as_class() {
+            class_list.add_1(class).expect("valid call");
+        }
+    });
+
+    // The word-break mode reaches every `line_text` through body-level CSS,
+    // like the control density above.
+    let (wrap_mode, _, _) = use_local_storage::<WrapMode, JsonCodec>("wrap-mode");
+    create_effect(move |_| {
+        let class_list = document().body().expect("body exists").class_list();
+        class_list
+            .remove_3("wrap_break_all", "wrap_keep_all", "wrap_scroll")
+            .expect("valid call");
+        if let Some(class) = wrap_mode.get().as_class() {
+            class_list.add_1(class).expect("valid call");
+        }
+    });
+
+    // Zen mode hides every piece of chrome, so it has to be applied where the
+    // chrome can't cover it: as a class on the body itself. Overlay mode
+    // implies it.
+    create_effect(move |_| {
+        document()
+            .body()
+            .expect("body exists")
+            .class_list()
+            .toggle_with_force("zen", zen.get() || overlay.is_some())
+            .expect("valid call");
+    });
+    if overlay.is_some() {
+        document()
+            .body()
+            .expect("body exists")
+            .class_list()
+            .add_1("overlay")
+            .expect("valid call");
+    }
+
+    // Split view narrows the main log from body-level CSS, like zen mode.
+    create_effect(move |_| {
+        document()
+            .body()
+            .expect("body exists")
+            .class_list()
+            .toggle_with_force("split", split_view.get())
+            .expect("valid call");
+    });
+
+    // Kept in sync through `fullscreenchange` so Escape and F11 exits are
+    // reflected in the toolbar icon.
+    let fullscreen = create_rw_signal(false);
+    let _ = use_event_listener(document(), ev::fullscreenchange, move |_| {
+        fullscreen.set(document().fullscreen_element().is_some());
+    });
+    let toggle_fullscreen = move || {
+        if document().fullscreen_element().is_some() {
+            document().exit_fullscreen();
+        } else {
+            document()
+                .document_element()
+                .expect("document element exists")
+                .request_fullscreen()
+                .expect("valid call");
+        }
+    };
+
+    let selected_text = use_selected_text();
+    let (filter_lookups, _, _) = use_local_storage::<bool, JsonCodec>("filter-lookups");
+
+    #[cfg(feature = "dictionary")]
+    let dictionary_popup = view! { <dictionary::DictionaryPopup selected_text/> };
+    #[cfg(not(feature = "dictionary"))]
+    let dictionary_popup = ();
+
+    // Yomitan-style popups can re-emit a looked-up word as a fresh <p> node a
+    // moment after the selection is gone, so remember what was selected
+    // recently, not just what is selected now.
+    let recent_lookups = store_value(Vec::<(f64, String)>::new());
+    create_effect(move |_| {
+        let Some(selected) = selected_text.get() else {
+            return;
+        };
+        if selected.is_empty() {
+            return;
+        }
+        let now = js_sys::Date::now();
+        recent_lookups.update_value(|recent| {
+            recent.retain(|(at, _)| now - *at < LOOKUP_FILTER_WINDOW_MS);
+            recent.push((now, selected));
+        });
+    });
+
+    let toasts = create_rw_signal(Vec::<Toast>::new());
+    let toast_id = store_value(0_usize);
+    let push_toast = move |message: String, undoable: bool| {
+        let id = toast_id.get_value();
+        toast_id.set_value(id + 1);
+        let undo_len = undoable.then(|| undo_stack.with_untracked(UndoStack::undo_depth));
+        toasts.update(|toasts| toasts.push(Toast { id, message, undo_len }));
+        set_timeout(
+            move || toasts.update(|toasts| toasts.retain(|toast| toast.id != id)),
+            Duration::from_secs(5),
+        );
+    };
+
+    let scroll_to_bottom = move || {
+        let body = document().body().expect("body exists");
+        window().scroll_to_with_x_and_y(0.0, body.scroll_height() as f64);
+    };
+
+    // The diagnostics event log, fed by the ingestion, websocket, and
+    // storage paths below and shown in the diagnostics modal.
+    let diagnostics = create_rw_signal(Vec::<DiagEvent>::new());
+    let diag_id = store_value(0_usize);
+    let diag = move |message: String| {
+        let id = diag_id.get_value();
+        diag_id.set_value(id + 1);
+        diagnostics.update(|events| {
+            events.push(DiagEvent { id, at: js_sys::Date::now(), message });
+            if events.len() > DIAGNOSTICS_CAP {
+                events.remove(0);
+            }
+        });
+    };
+    // Filter reasons quote the line, trimmed so one huge line can't flood
+    // the panel.
+    let diag_snippet = |text: &str| -> String {
+        let snippet: String = text.chars().take(24).collect();
+        if snippet.len() < text.len() {
+            format!("{snippet}…")
+        } else {
+            snippet
+        }
+    };
+
+    // Outbound event broadcast so downstream tools (stat trackers, overlay
+    // renderers) can subscribe to this page as the source of truth. Each
+    // frame is `{"event": "added" | "edited" | "removed", "id": <usize>,
+    // "text": <string>}`; `text` is the new text, or the last text for
+    // removals.
+    let (broadcast_url, _, _) = use_local_storage::<String, JsonCodec>("broadcast-url");
+    let broadcast_socket = store_value(None::<web_sys::WebSocket>);
+    {
+        let url = broadcast_url.get_untracked();
+        if !url.is_empty() {
+            broadcast_socket.set_value(web_sys::WebSocket::new(&url).ok());
+        }
+    }
+    let broadcast = move |event: &str, id: usize, text: &str| {
+        broadcast_socket.with_value(|socket| {
+            let Some(socket) = socket else {
+                return;
+            };
+            if socket.ready_state() == web_sys::WebSocket::OPEN {
+                let frame =
+                    serde_json::json!({ "event": event, "id": id, "text": text }).to_string();
+                let _ = socket.send_with_str(&frame);
+            }
+        });
+    };
+
+    // Live feed for `?mirror` tabs: the whole line set goes out on every
+    // change. Mirrors are passive, so full snapshots beat maintaining a
+    // delta protocol; the serialized form is the storage codec's anyway.
+    if let Ok(channel) = web_sys::BroadcastChannel::new(MIRROR_CHANNEL) {
+        let channel = store_value(channel);
+        create_effect(move |_| {
+            let json = lines.with(|lines| serde_json::to_string(lines).expect("valid json"));
+            channel.with_value(|channel| {
+                let _ = channel.post_message(&JsValue::from_str(&json));
+            });
+        });
+    }
+
+    // Per-line text-to-speech through the Web Speech API.
+    let (tts_voice, _, _) = use_local_storage::<String, JsonCodec>("tts-voice");
+    let (tts_rate, _, _) = use_local_storage::<f32, JsonCodec>("tts-rate");
+    let (tts_auto, _, _) = use_local_storage::<bool, JsonCodec>("tts-auto");
+    let speak_line = move |id: usize| {
+        let Some(text) = lines.with_untracked(|lines| lines.get(&id).map(|line| line.text.clone()))
+        else {
+            return;
+        };
+        speak(&text, &tts_voice.get_untracked(), tts_rate.get_untracked());
+    };
+
+    // Audible ping for second-machine setups where the hooker window is
+    // never the focused one: a custom uploaded sound, or a short
+    // synthesized beep when none is set.
+    let (notify_sound, _, _) = use_local_storage::<bool, JsonCodec>("notify-sound");
+    let (notify_sound_data, _, _) = use_local_storage::<String, JsonCodec>("notify-sound-data");
+
+    // Desktop notifications for lines arriving while the tab is hidden.
+    // The permission prompt is raised when the toggle is first enabled;
+    // the separate mute covers quiet hours without touching the browser
+    // permission state.
+    let (desktop_notify, _, _) = use_local_storage::<bool, JsonCodec>("desktop-notifications");
+    let (notify_muted, _, _) = use_local_storage::<bool, JsonCodec>("notifications-muted");
+    create_effect(move |_| {
+        if desktop_notify.get()
+            && web_sys::Notification::permission() == web_sys::NotificationPermission::Default
+        {
+            let Ok(promise) = web_sys::Notification::request_permission() else {
+                return;
+            };
+            spawn_local(async move {
+                let _ = JsFuture::from(promise).await;
+            });
+        }
+    });
+
+    // The flush itself, plus the bookkeeping choosing when it runs. The
+    // interval length is read once at startup, like the stats push.
+    let save_now = move || {
+        let started = js_sys::Date::now();
+        set_stored_lines.set(lines.get_untracked());
+        dirty.set(false);
+        diag(format!(
+            "flushed the log to storage in {:.1} ms",
+            js_sys::Date::now() - started,
+        ));
+    };
+    create_effect(move |prev| {
+        lines.track();
+        if prev.is_none() {
+            return;
+        }
+        match save_policy.get_untracked() {
+            SavePolicy::Immediate => save_now(),
+            SavePolicy::Interval | SavePolicy::Manual => dirty.set(true),
+        }
+    });
+    {
+        let interval = match autosave_interval.get_untracked() {
+            0 => AUTOSAVE_DEFAULT_INTERVAL_SECS,
+            interval => interval,
+        };
+        set_interval(
+            move || {
+                if save_policy.get_untracked() == SavePolicy::Interval && dirty.get_untracked() {
+                    save_now();
+                }
+            },
+            Duration::from_secs(interval.into()),
+        );
+    }
+    // Whatever the policy, don't lose unsaved lines to a closing tab.
+    let _ = use_event_listener(window(), ev::beforeunload, move |_| {
+        if dirty.get_untracked() {
+            save_now();
+        }
+    });
+
+    // Marathon sessions eventually hold more lines than the DOM and the
+    // storage codec stay snappy with; past the cap the oldest unlocked
+    // lines move out of the live map into the archive store, which is
+    // never rendered. Zero means no cap.
+    let (max_active_lines, _, _) = use_local_storage::<u32, JsonCodec>("max-active-lines");
+    let (_, set_archived, _) = use_local_storage::<LineMap, JsonCodec>("lines-archive");
+
+    // A hook stuck in a loop can emit hundreds of lines a second, burying
+    // the real text and grinding the page down with storage writes. Any
+    // source exceeding the flood threshold gets held at the door: ingestion
+    // pauses and the burst accumulates until the user chooses to resume or
+    // discard it.
+    let flood_times = store_value(Vec::<f64>::new());
+    let flood_held = create_rw_signal(Vec::<String>::new());
+    let flooded = create_rw_signal(false);
+
+    // Bursts (backlog dumps, multi-node mutation records) land as one batch:
+    // a single `set_lines` write, a single undo entry, and a single scroll,
+    // rather than N reactive updates. `guarded` is false only when a held
+    // burst is resumed, so releasing it can't re-trip the flood guard.
+    let ingest = move |texts: Vec<String>, guarded: bool| {
+        if paused.get_untracked() {
+            diag(format!("dropped {} line(s): capture paused", texts.len()));
+            return;
+        }
+        if guarded {
+            if flooded.get_untracked() {
+                flood_held.update(|held| held.extend(texts));
+                return;
+            }
+            let now = js_sys::Date::now();
+            let over = flood_times
+                .try_update_value(|times| {
+                    times.retain(|at| now - at < FLOOD_WINDOW_MS);
+                    times.resize(times.len() + texts.len(), now);
+                    times.len() > FLOOD_THRESHOLD
+                })
+                .expect("update succeeds");
+            if over {
+                diag(format!(
+                    "flood guard tripped: over {FLOOD_THRESHOLD} line(s) within a second"
+                ));
+                flooded.set(true);
+                flood_held.update(|held| held.extend(texts));
+                return;
+            }
+        }
+        // The secondary workspace is a plain list: no undo entry and no
+        // ingest rules, so routed lines take the short path.
+        if split_view.get_untracked() && ingest_secondary.get_untracked() {
+            diag(format!("routed {} line(s) to the secondary workspace", texts.len()));
+            add_secondary(texts);
+            return;
+        }
+        // Some hooks emit whole paragraphs, unwieldy for mining; optionally
+        // break them into one line per sentence before the per-line rules
+        // run.
+        let texts = if sentence_split.get_untracked() {
+            texts.iter().flat_map(|text| split_sentences(text)).collect()
+        } else {
+            texts
+        };
+        let mut batch = Vec::<(usize, Line)>::new();
+        // Pending prefix-extension replacement of the line that was already
+        // the tail of the map when the batch started: id, the text to
+        // restore on undo, and the replacement.
+        let mut extend = None::<(usize, String, String)>;
+        // A locked tail never merges: the lock protects curated text from
+        // ingest-side rewrites just as it does from manual edits.
+        let map_tail = merge_extensions
+            .get_untracked()
+            .then(|| {
+                lines.with_untracked(|lines| {
+                    lines
+                        .last()
+                        .filter(|(_, line)| !line.locked)
+                        .map(|(&id, line)| (id, line.text.clone()))
+                })
+            })
+            .flatten();
+        let mut merged = 0_usize;
+        for text in texts {
+            // The clipboard inserter re-emits text selected for dictionary
+            // lookups; drop anything that matches the current selection,
+            // read live since the tracked signal is debounced.
+            if current_selection().is_some_and(|s| s == text) {
+                diag(format!("filtered (matches selection): {}", diag_snippet(&text)));
+                continue;
+            }
+            // Short lines that sit inside a recent selection are lookup
+            // echoes.
+            if filter_lookups.get_untracked() {
+                let now = js_sys::Date::now();
+                let echoed =
+                    recent_lookups.with_value(|recent| is_lookup_echo(&text, now, recent));
+                if echoed {
+                    diag(format!("filtered (recent lookup echo): {}", diag_snippet(&text)));
+                    continue;
+                }
+            }
+            // Engine debug strings and status messages contain no
+            // target-script characters; drop them outright, or keep them
+            // tagged and dimmed for sources that are only mostly noise.
+            let mode = language_filter.get_untracked();
+            let mut flag_language = false;
+            if mode != LanguageFilter::Off
+                && !contains_script(&text, target_script.get_untracked())
+            {
+                if mode == LanguageFilter::Drop {
+                    diag(format!(
+                        "filtered (no target-script characters): {}",
+                        diag_snippet(&text)
+                    ));
+                    continue;
+                }
+                flag_language = true;
+            }
+            let text = if strip_furigana.get_untracked() {
+                strip_parenthesized_furigana(&text)
+            } else {
+                text
+            };
+            // The split-off speaker name is kept on the line for the
+            // color tinting, the speaker column of delimited exports, and
+            // filtering.
+            let mut speaker = None;
+            let text = if strip_speaker.get_untracked() {
+                let pairs = or_default(speaker_brackets.get_untracked(), SPEAKER_BRACKETS_DEFAULT);
+                match split_speaker_marker(&text, &pairs) {
+                    Some((name, rest)) => {
+                        speaker = (!name.is_empty()).then_some(name);
+                        rest
+                    }
+                    None => text,
+                }
+            } else {
+                text
+            };
+            // Engines that re-send the whole textbox every character tick
+            // produce lines that strictly extend the previous one; replace
+            // that line so only the final full sentence remains.
+            if merge_extensions.get_untracked() {
+                if let Some((id, line)) = batch.last_mut() {
+                    if strictly_extends(&text, &line.text) {
+                        line.text = text;
+                        broadcast("edited", *id, &line.text);
+                        merged += 1;
+                        continue;
+                    }
+                } else if let Some((id, _, new)) = &mut extend {
+                    if strictly_extends(&text, new) {
+                        *new = text;
+                        broadcast("edited", *id, new);
+                        merged += 1;
+                        continue;
+                    }
+                } else if let Some((id, tail)) = &map_tail {
+                    if strictly_extends(&text, tail) {
+                        broadcast("edited", *id, &text);
+                        extend = Some((*id, tail.clone(), text));
+                        merged += 1;
+                        continue;
+                    }
+                }
+            }
+            let id = alloc_id();
+            broadcast("added", id, &text);
+            if tts_auto.get_untracked() {
+                speak(&text, &tts_voice.get_untracked(), tts_rate.get_untracked());
+            }
+            let mut line = Line::new(text, Some(js_sys::Date::now()));
+            line.speaker = speaker;
+            if flag_language {
+                line.tags.push(LANGUAGE_FLAG_TAG.to_string());
+            }
+            batch.push((id, line));
+        }
+        let last_id = match (batch.last(), &extend) {
+            (Some(&(id, _)), _) => id,
+            (None, Some((id, _, _))) => *id,
+            (None, None) => return,
+        };
+        let ids = batch.iter().map(|(id, _)| *id).collect::<Vec<_>>();
+        set_lines.update(|lines| {
+            if let Some((id, _, new)) = &extend {
+                if let Some(line) = lines.get_mut(id) {
+                    line.text = new.clone();
+                    line.version += 1;
+                }
+            }
+            for (id, line) in batch {
+                lines.insert(id, line);
+            }
+        });
+        diag(format!("accepted {} line(s)", ids.len()));
+        if merged > 0 {
+            diag(format!("merged {merged} textbox tick(s) into the previous line"));
+        }
+        // Roll overflow past the active cap into the archive. The removals
+        // are recorded in the same undo step as the inserts, since any
+        // unrecorded removal would drift the history's indices.
+        let cap = max_active_lines.get_untracked() as usize;
+        let mut archived_inverses = Vec::<Operation>::new();
+        if cap > 0 {
+            let overflow: Vec<usize> = lines.with_untracked(|lines| {
+                let excess = lines.len().saturating_sub(cap);
+                lines
+                    .iter()
+                    .filter(|(_, line)| !line.locked)
+                    .map(|(&id, _)| id)
+                    .take(excess)
+                    .collect()
+            });
+            if !overflow.is_empty() {
+                set_archived.update(|archive| {
+                    set_lines.update(|lines| {
+                        for &id in &overflow {
+                            let (index, id, line) =
+                                lines.shift_remove_full(&id).expect("line exists");
+                            archive.insert(id, line.clone());
+                            archived_inverses.push(Operation::Insert { id, index, line });
+                        }
+                    });
+                });
+                diag(format!("archived {} line(s) past the active cap", overflow.len()));
+            }
+        }
+        // Inverses in reverse order of application: the archived lines come
+        // back first, then the inserts are undone, then the tail's text is
+        // restored.
+        let mut inverses: Vec<Operation> = archived_inverses;
+        inverses.reverse();
+        inverses.extend(ids.iter().rev().map(|&id| Operation::Remove { id }));
+        if let Some((id, old, _)) = extend {
+            inverses.push(Operation::SetText { id, text: old });
+        }
+        undo_stack.update(|stack| {
+            stack.push(match inverses.len() {
+                1 => inverses.pop().expect("one inverse"),
+                _ => Operation::Batch { operations: inverses },
+            })
+        });
+        newest_id.set(Some(last_id));
+        if notify_sound.get_untracked() && !document().has_focus().unwrap_or(true) {
+            play_notification(&notify_sound_data.get_untracked());
+        }
+        if desktop_notify.get_untracked()
+            && !notify_muted.get_untracked()
+            && document().hidden()
+            && web_sys::Notification::permission() == web_sys::NotificationPermission::Granted
+        {
+            let body = lines.with_untracked(|lines| {
+                lines.get(&last_id).map(|line| line.text.clone()).unwrap_or_default()
+            });
+            let body = match ids.len() {
+                0 | 1 => body,
+                count => format!("{body} (+{} more)", count - 1),
+            };
+            // One tag so a burst replaces its own notification rather
+            // than stacking.
+            let mut options = web_sys::NotificationOptions::new();
+            options.body(&body).tag("texthooker");
+            let _ = web_sys::Notification::new_with_options("Texthooker", &options);
+        }
+        // With scroll lock on, arriving lines must not yank the line being
+        // edited out of view.
+        if !(scroll_lock_editing.get_untracked() && focused_id.get_untracked().is_some()) {
+            scroll_to_bottom();
+        }
+    };
+    let add_lines = move |texts: Vec<String>| ingest(texts, true);
+    let add_line = move |text: String| add_lines(vec![text]);
+
+    // The "resume" choice releases the held burst in one unguarded batch;
+    // "discard" throws it away. Both re-arm the guard.
+    let flood_resume = move || {
+        flood_times.set_value(Vec::new());
+        flooded.set(false);
+        let held = flood_held.try_update(std::mem::take).expect("update succeeds");
+        diag(format!("flood guard resumed: ingesting {} held line(s)", held.len()));
+        ingest(held, false);
+    };
+    let flood_discard = move || {
+        flood_times.set_value(Vec::new());
+        flooded.set(false);
+        let held = flood_held.try_update(std::mem::take).expect("update succeeds");
+        diag(format!("flood guard discarded {} held line(s)", held.len()));
+    };
+
+    // Locked lines refuse edits and removal until unlocked; the checks sit
+    // here so keyboard shortcuts and swipes are covered, not just the
+    // hidden buttons.
+    let line_locked = move |id: usize| {
+        lines.with_untracked(|lines| lines.get(&id).is_some_and(|line| line.locked))
+    };
+    let toggle_lock = move |id: usize| {
+        set_lines.update(|lines| {
+            let Some(line) = lines.get_mut(&id) else {
+                return;
+            };
+            line.locked = !line.locked;
+            line.version += 1;
+        });
+    };
+
+    let remove = move |id: usize| {
+        if read_only.get_untracked() {
+            push_toast("Log is read-only".to_string(), false);
+            return;
+        }
+        if line_locked(id) {
+            push_toast("Line is locked".to_string(), false);
+            return;
+        }
+        let (index, _, line) = set_lines
+            .try_update(|lines| lines.shift_remove_full(&id))
+            .flatten()
+            .expect("line exists");
+        broadcast("removed", id, &line.text);
+        undo_stack.update(|stack| stack.push(Operation::Insert { id, index, line }));
+        push_toast("Line deleted".to_string(), true);
+    };
+
+    // Inserts a fresh copy right after the original, for keeping the raw
+    // hooked text while editing a cleaned-up version next to it. The copy
+    // starts untagged with its own timestamp.
+    let duplicate_line = move |id: usize| {
+        if read_only.get_untracked() {
+            push_toast("Log is read-only".to_string(), false);
+            return;
+        }
+        let Some((index, text)) = lines.with_untracked(|lines| {
+            lines.get_full(&id).map(|(index, _, line)| (index, line.text.clone()))
+        }) else {
+            return;
+        };
+        let copy_id = alloc_id();
+        broadcast("added", copy_id, &text);
+        set_lines.update(|lines| {
+            lines.shift_insert(index + 1, copy_id, Line::new(text, Some(js_sys::Date::now())));
+        });
+        undo_stack.update(|stack| stack.push(Operation::Remove { id: copy_id }));
+    };
+
+    let set_text = move |(id, text): (usize, String)| {
+        if read_only.get_untracked() {
+            push_toast("Log is read-only".to_string(), false);
+            return;
+        }
+        if line_locked(id) {
+            push_toast("Line is locked".to_string(), false);
+            return;
+        }
+        broadcast("edited", id, &text);
+        let old = set_lines
+            .try_update(|lines| {
+                let line = lines.get_mut(&id).expect("line exists");
+                let old = std::mem::replace(&mut line.text, text);
+                line.version += 1;
+                old
+            })
+            .expect("update succeeds");
+        // Coalesced, so a run of per-blur micro-edits undoes as one step.
+        undo_stack.update(|stack| stack.push_edit(id, old, js_sys::Date::now()));
+    };
+
+    let clear = move || {
+        let old = set_lines
+            .try_update(std::mem::take)
+            .expect("update succeeds");
+        if !old.is_empty() {
+            let count = old.len();
+            undo_stack.update(|stack| stack.push(Operation::Replace { lines: old }));
+            push_toast(format!("Cleared {count} lines"), true);
+        }
+    };
+
+    // Dialogs take focus while open and hand it back to the invoking element
+    // when they close, so the app stays usable without a mouse.
+    let modal_return_focus = store_value(None::<web_sys::HtmlElement>);
+    let remember_focus = move || {
+        modal_return_focus.set_value(
+            document()
+                .active_element()
+                .and_then(|el| el.dyn_into::<web_sys::HtmlElement>().ok()),
+        );
+    };
+    let restore_focus = move || {
+        if let Some(element) = modal_return_focus.try_update_value(Option::take).flatten() {
+            element.focus().expect("focus should not fail");
+        }
+    };
+
+    let confirm_clear_open = create_rw_signal(false);
+    let request_clear = move || {
+        if read_only.get_untracked() {
+            push_toast("Log is read-only".to_string(), false);
+            return;
+        }
+        if skip_clear_confirm.get_untracked() || lines.with_untracked(|lines| lines.is_empty()) {
+            clear();
+        } else {
+            remember_focus();
+            confirm_clear_open.set(true);
+        }
+    };
+
+    let (context_lines, _, _) = use_local_storage::<u32, JsonCodec>("context-lines");
+    let (copy_with_context, _, _) = use_local_storage::<bool, JsonCodec>("copy-with-context");
+
+    // A line joined with its `n` surrounding lines on each side.
+    let line_with_context = move |id: usize, n: usize| {
+        lines.with_untracked(|lines| {
+            let index = lines.get_index_of(&id)?;
+            let start = index.saturating_sub(n);
+            let end = (index + n).min(lines.len() - 1);
+            Some(
+                lines
+                    .values()
+                    .skip(start)
+                    .take(end - start + 1)
+                    .map(|line| line.text.as_str())
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            )
+        })
+    };
+
+    // All clipboard copies funnel through here so the context setting applies
+    // uniformly.
+    let copy_line = move |id: usize| {
+        let n = if copy_with_context.get_untracked() {
+            context_lines.get_untracked() as usize
+        } else {
+            0
+        };
+        if let Some(text) = line_with_context(id, n) {
+            clipboard_write_text(&text);
+        }
+    };
+
+    // Toggles the mining tag the Anki TSV export picks up.
+    let (anki_export_tag, _, _) = use_local_storage::<String, JsonCodec>("anki-export-tag");
+    let toggle_tag = move |id: usize| {
+        if read_only.get_untracked() {
+            push_toast("Log is read-only".to_string(), false);
+            return;
+        }
+        let tag = or_default(anki_export_tag.get_untracked(), ANKI_EXPORT_DEFAULT_TAG);
+        set_lines.update(|lines| {
+            let Some(line) = lines.get_mut(&id) else {
+                return;
+            };
+            match line.tags.iter().position(|t| t == &tag) {
+                Some(index) => {
+                    line.tags.remove(index);
+                }
+                None => line.tags.push(tag),
+            }
+            line.version += 1;
+        });
+    };
+
+    // The whole AnkiConnect/JPDB mining integration is optional
+    // (`--no-default-features` drops it); the stubs below keep the
+    // `LineView` props satisfied while its buttons are compiled out.
+    #[cfg(feature = "anki")]
+    let (send_to_anki, create_anki_note, jpdb_enabled, send_to_jpdb) = {
+        let (anki_url, _, _) = use_local_storage::<String, JsonCodec>("ankiconnect-url");
+        let (anki_sentence_field, _, _) =
+            use_local_storage::<String, JsonCodec>("anki-sentence-field");
+        let (anki_mappings, _, _) =
+            use_local_storage::<Vec<FieldMapping>, JsonCodec>("anki-field-mappings");
+        let (session_title, _, _) = use_local_storage::<String, JsonCodec>("session-title");
+
+        // Everything a note mapping can pull from, gathered at send time.
+        let anki_payload = move |id: usize| {
+            let context = line_with_context(id, context_lines.get_untracked() as usize)?;
+            lines.with_untracked(|lines| {
+                let line = lines.get(&id).expect("line exists");
+                Some(AnkiPayload {
+                    sentence: line.text.clone(),
+                    context,
+                    session: session_title.get_untracked(),
+                    timestamp: String::from(js_sys::Date::new_0().to_iso_string()),
+                })
+            })
+        };
+        let anki_fields = move |payload: &AnkiPayload| {
+            let field = or_default(
+                anki_sentence_field.get_untracked(),
+                ANKI_DEFAULT_SENTENCE_FIELD,
+            );
+            anki_mappings.with_untracked(|mappings| mapped_fields(mappings, &field, payload))
+        };
+
+        // Writes a line into the sentence field of the newest Anki note, the
+        // "mine the word first, fix the sentence after" workflow.
+        let send_to_anki = move |id: usize| {
+            let Some(payload) = anki_payload(id) else {
+                return;
+            };
+            let url = or_default(anki_url.get_untracked(), ANKI_CONNECT_DEFAULT_URL);
+            let fields = anki_fields(&payload);
+            spawn_local(async move {
+                match update_latest_note(&url, fields).await {
+                    Ok(()) => push_toast("Sentence sent to Anki".to_string(), false),
+                    Err(error) => push_toast(format!("AnkiConnect: {error}"), false),
+                }
+            });
+        };
+
+        let (anki_deck, _, _) = use_local_storage::<String, JsonCodec>("anki-deck");
+        let (anki_note_type, _, _) = use_local_storage::<String, JsonCodec>("anki-note-type");
+        // Builds a whole new note from a line instead of patching the newest one.
+        let create_anki_note = move |id: usize| {
+            let Some(payload) = anki_payload(id) else {
+                return;
+            };
+            let url = or_default(anki_url.get_untracked(), ANKI_CONNECT_DEFAULT_URL);
+            let deck = or_default(anki_deck.get_untracked(), ANKI_DEFAULT_DECK);
+            let note_type = or_default(anki_note_type.get_untracked(), ANKI_DEFAULT_NOTE_TYPE);
+            let fields = anki_fields(&payload);
+            spawn_local(async move {
+                match create_note(&url, &deck, &note_type, fields).await {
+                    Ok(()) => push_toast("Anki card created".to_string(), false),
+                    Err(error) => push_toast(format!("AnkiConnect: {error}"), false),
+                }
+            });
+        };
+
+        let (jpdb_api_key, _, _) = use_local_storage::<String, JsonCodec>("jpdb-api-key");
+        // Sets a line as the JPDB example sentence for the selected word (or the
+        // line's first word when nothing is selected). Only offered once an API
+        // key is configured.
+        let jpdb_enabled = Signal::derive(move || !jpdb_api_key.get().is_empty());
+        let send_to_jpdb = move |id: usize| {
+            let Some(sentence) =
+                lines.with_untracked(|lines| lines.get(&id).map(|line| line.text.clone()))
+            else {
+                return;
+            };
+            let api_key = jpdb_api_key.get_untracked();
+            let word = selected_text
+                .get_untracked()
+                .filter(|selected| !selected.is_empty())
+                .unwrap_or_else(|| sentence.clone());
+            spawn_local(async move {
+                match jpdb_set_sentence(&api_key, &word, &sentence).await {
+                    Ok(()) => push_toast("Sentence sent to JPDB".to_string(), false),
+                    Err(error) => push_toast(format!("JPDB: {error}"), false),
+                }
+            });
+        };
+        (send_to_anki, create_anki_note, jpdb_enabled, send_to_jpdb)
+    };
+    #[cfg(not(feature = "anki"))]
+    let (send_to_anki, create_anki_note, jpdb_enabled, send_to_jpdb) = (
+        |_: usize| {},
+        |_: usize| {},
+        Signal::derive(|| false),
+        |_: usize| {},
+    );
+
+    // Multi-select: Ctrl-click (Cmd on macOS) toggles a line and anchors the
+    // range; Shift-click selects everything between the anchor and the target.
+    let selection = create_rw_signal(HashSet::<usize>::new());
+    provide_context(SelectedLines(selection));
+    let select_anchor = store_value(None::<usize>);
+    let select = move |(id, range): (usize, bool)| {
+        if range {
+            let anchor = select_anchor.get_value().unwrap_or(id);
+            let ids: Vec<usize> = lines.with_untracked(|lines| {
+                match (lines.get_index_of(&anchor), lines.get_index_of(&id)) {
+                    (Some(a), Some(b)) => {
+                        let (lo, hi) = if a <= b { (a, b) } else { (b, a) };
+                        lines.keys().copied().skip(lo).take(hi - lo + 1).collect()
+                    }
+                    _ => vec![id],
+                }
+            });
+            selection.update(|selection| selection.extend(ids));
+        } else {
+            selection.update(|selection| {
+                if !selection.insert(id) {
+                    selection.remove(&id);
+                }
+            });
+            select_anchor.set_value(Some(id));
+        }
+    };
+
+    // Self-hosted sync: push uploads the full line set, pull merges the
+    // server's copy in through [`merge_lines`].
+    let (sync_url, _, _) = use_local_storage::<String, JsonCodec>("sync-url");
+    let sync_push = move || {
+        let url = sync_url.get_untracked();
+        let json = serde_json::to_string(&lines.get_untracked()).expect("valid json");
+        spawn_local(async move {
+            http_post(&url, &json).await;
+        });
+        push_toast("Pushed to sync server".to_string(), false);
+    };
+    let sync_pull = move || {
+        let url = sync_url.get_untracked();
+        spawn_local(async move {
+            let remote = http_get_json(&url)
+                .await
+                .and_then(|value| serde_json::from_value::<LineMap>(value).ok());
+            let Some(remote) = remote else {
+                push_toast("Sync pull failed".to_string(), false);
+                return;
+            };
+            set_lines.update(|lines| merge_lines(lines, remote));
+            // Keep the id allocator ahead of any merged-in ids.
+            let max_id = lines.with_untracked(texthooker_core::next_id);
+            next_id.set_value(next_id.get_value().max(max_id));
+            push_toast("Pulled from sync server".to_string(), false);
+        });
+    };
+
+    // Periodic WebDAV backup: the full line set is PUT to the configured URL
+    // so backups leave the browser without a custom server.
+    let (webdav_url, _, _) = use_local_storage::<String, JsonCodec>("webdav-url");
+    let (webdav_username, _, _) = use_local_storage::<String, JsonCodec>("webdav-username");
+    let (webdav_password, _, _) = use_local_storage::<String, JsonCodec>("webdav-password");
+    if !webdav_url.get_untracked().is_empty() {
+        set_interval(
+            move || {
+                let url = webdav_url.get_untracked();
+                let username = webdav_username.get_untracked();
+                let password = webdav_password.get_untracked();
+                let json = serde_json::to_string(&lines.get_untracked()).expect("valid json");
+                spawn_local(async move {
+                    webdav_put(&url, &username, &password, &json).await;
+                });
+            },
+            Duration::from_secs(WEBDAV_BACKUP_INTERVAL_SECS),
+        );
+    }
+
+    // Packs the selected lines into a read-only snapshot link and copies it.
+    let share_selection = move || {
+        let texts: Vec<String> = lines.with_untracked(|lines| {
+            selection.with_untracked(|selection| {
+                lines
+                    .iter()
+                    .filter(|(id, _)| selection.contains(id))
+                    .map(|(_, line)| line.text.clone())
+                    .collect()
+            })
+        });
+        if texts.is_empty() {
+            push_toast("Select lines to share".to_string(), false);
+            return;
+        }
+        let location = window().location();
+        let origin = location.origin().expect("valid call");
+        let pathname = location.pathname().expect("valid call");
+        let url = format!("{origin}{pathname}#share={}", encode_snapshot(&texts));
+        clipboard_write_text(&url);
+        push_toast("Share link copied".to_string(), false);
+    };
+
+    // The mobile quick-add input bypasses the capture filters: typed text is
+    // always wanted.
+    let quick_add = move |ev: KeyboardEvent| {
+        if ev.key() != "Enter" || ev.is_composing() {
+            return;
+        }
+        let input = event_target::<web_sys::HtmlInputElement>(&ev);
+        let text = input.value();
+        if text.is_empty() {
+            return;
+        }
+        input.set_value("");
+        let id = alloc_id();
+        set_lines.update(|lines| {
+            lines.insert(id, Line::new(text, Some(js_sys::Date::now())));
+        });
+        undo_stack.update(|stack| stack.push(Operation::Remove { id }));
+        newest_id.set(Some(id));
+        scroll_to_bottom();
+    };
+
+    let add_focused_entry = move || {
+        // A fresh entry opens straight into edit mode, which the lock
+        // forbids.
+        if read_only.get_untracked() {
+            push_toast("Log is read-only".to_string(), false);
+            return;
+        }
+        let id = alloc_id();
+        set_lines.update(|lines| {
+            lines.insert(id, Line::default());
+        });
+        undo_stack.update(|stack| stack.push(Operation::Remove { id }));
+        pending_focus.set(Some(id));
+        scroll_to_bottom();
+    };
+
+    let undo = move || {
+        if read_only.get_untracked() || !undo_stack.with_untracked(UndoStack::can_undo) {
+            return;
+        }
+        undo_stack.update(|stack| {
+            set_lines.update(|lines| {
+                stack.undo(lines);
+            });
+        });
+    };
+
+    let redo = move || {
+        if read_only.get_untracked() || !undo_stack.with_untracked(UndoStack::can_redo) {
+            return;
+        }
+        undo_stack.update(|stack| {
+            set_lines.update(|lines| {
+                stack.redo(lines);
+            });
+        });
+    };
+
+    let download = move || {
+        let json = serde_json::to_string(&lines.get_untracked()).expect("valid json");
+        download_text("texthooker.json", &json);
+    };
+
+    // Pausing capture disconnects the observer outright (the pause check in
+    // `add_lines` still covers the other ingestion paths), and changing the
+    // target selector re-attaches it without a reload.
+    let observer = store_value(ObserverHandle::new(add_lines));
+    let (capture_selector, _, _) = use_local_storage::<String, JsonCodec>("capture-selector");
+    create_effect(move |_| {
+        if paused.get() {
+            observer.update_value(ObserverHandle::disconnect);
+        } else {
+            let selector = capture_selector.get();
+            observer.update_value(|observer| observer.observe(&selector));
+        }
+    });
+
+    let (websocket_url, _, _) = use_local_storage::<String, JsonCodec>("websocket-url");
+    let ws_url = websocket_url.get_untracked();
+    if !ws_url.is_empty() {
+        setup_websocket(&ws_url, add_line, diag);
+    }
+
+    // Keeps the screen awake while the toggle is on, for a tablet or second
+    // monitor displaying the log mid-game. The OS drops the lock whenever
+    // the tab is hidden, so it is re-requested once the tab is visible
+    // again.
+    let (wake_lock, _, _) = use_local_storage::<bool, JsonCodec>("wake-lock");
+    let wake_sentinel = store_value(None::<JsValue>);
+    let acquire_wake_lock = move || {
+        spawn_local(async move {
+            if let Ok(sentinel) = wake_lock_request("screen").await {
+                wake_sentinel.set_value(Some(sentinel));
+            }
+        });
+    };
+    create_effect(move |_| {
+        if wake_lock.get() {
+            acquire_wake_lock();
+        } else if let Some(sentinel) = wake_sentinel.try_update_value(Option::take).flatten() {
+            release_wake_lock(&sentinel);
+        }
+    });
+    let _ = use_event_listener(document(), ev::visibilitychange, move |_| {
+        if wake_lock.get_untracked() && !document().hidden() {
+            acquire_wake_lock();
+        }
+    });
+
+    // OCR ingestion (optional at compile time): pasted or dropped images
+    // go to the configured OCR server and come back as lines, covering
+    // games that can't be hooked.
+    #[cfg(feature = "ocr")]
+    {
+        let (ocr_url, _, _) = use_local_storage::<String, JsonCodec>("ocr-url");
+        let ocr_file = move |file: web_sys::File| {
+            let url = ocr_url.get_untracked();
+            if url.is_empty() {
+                return;
+            }
+            spawn_local(async move {
+                match ocr_image(&url, &file).await {
+                    Some(text) if !text.is_empty() => add_line(text),
+                    _ => push_toast("OCR failed".to_string(), false),
+                }
+            });
+        };
+        let _ = use_event_listener(document(), ev::paste, move |ev| {
+            let ev = ev.unchecked_into::<web_sys::ClipboardEvent>();
+            let Some(data) = ev.clipboard_data() else {
+                return;
+            };
+            let items = data.items();
+            for index in 0..items.length() {
+                let Some(item) = items.get(index) else {
+                    continue;
+                };
+                if item.kind() == "file" && item.type_().starts_with("image/") {
+                    if let Ok(Some(file)) = item.get_as_file() {
+                        ocr_file(file);
+                    }
+                }
+            }
+        });
+        let _ = use_event_listener(document(), ev::dragover, move |ev| ev.prevent_default());
+        let _ = use_event_listener(document(), ev::drop, move |ev| {
+            ev.prevent_default();
+            let Some(files) = ev.data_transfer().map(|data| data.files()) else {
+                return;
+            };
+            let Some(files) = files else {
+                return;
+            };
+            for index in 0..files.length() {
+                let Some(file) = files.get(index) else {
+                    continue;
+                };
+                if file.type_().starts_with("image/") {
+                    ocr_file(file);
+                }
+            }
+        });
+    }
+
+    // Periodically push session stats to an external dashboard: over a
+    // websocket for ws:// URLs, otherwise as an HTTP POST.
+    let (stats_push_url, _, _) = use_local_storage::<String, JsonCodec>("stats-push-url");
+    let (stats_push_interval, _, _) = use_local_storage::<u32, JsonCodec>("stats-push-interval");
+    let (stats_push_template, _, _) = use_local_storage::<String, JsonCodec>("stats-push-template");
+    let (imported_chars, _, _) = use_local_storage::<u64, JsonCodec>("imported-chars");
+    let session_start = js_sys::Date::now();
+    let push_url = stats_push_url.get_untracked();
+    if !push_url.is_empty() {
+        let stats_socket = push_url
+            .starts_with("ws")
+            .then(|| web_sys::WebSocket::new(&push_url).ok())
+            .flatten();
+        let interval = match stats_push_interval.get_untracked() {
+            0 => STATS_PUSH_DEFAULT_INTERVAL_SECS,
+            interval => interval,
+        };
+        set_interval(
+            move || {
+                let chars = lines.with_untracked(|lines| {
+                    lines
+                        .values()
+                        .map(|line| line.text.chars().count())
+                        .sum::<usize>()
+                }) + imported_chars.get_untracked() as usize;
+                let seconds = ((js_sys::Date::now() - session_start) / 1000.0) as u64;
+                let template =
+                    or_default(stats_push_template.get_untracked(), STATS_PUSH_DEFAULT_TEMPLATE);
+                let body =
+                    stats_payload(&template, chars, seconds, &session_title.get_untracked());
+                match &stats_socket {
+                    Some(socket) => {
+                        if socket.ready_state() == web_sys::WebSocket::OPEN {
+                            let _ = socket.send_with_str(&body);
+                        }
+                    }
+                    None => {
+                        let url = push_url.clone();
+                        spawn_local(async move { http_post(&url, &body).await });
+                    }
+                }
+            },
+            Duration::from_secs(interval.into()),
+        );
+    }
+
+    let advance_read_marker = move || {
+        // Advance the read marker to the next unread line.
+        let next = lines.with_untracked(|lines| {
+            let marker = read_marker.get_untracked();
+            lines
+                .keys()
+                .copied()
+                .find(|id| marker.is_none_or(|marker| *id > marker))
+        });
+        if next.is_some() {
+            set_read_marker.set(next);
+        }
+    };
+
+    let delete_newest = move || {
+        let Some(id) = lines.with_untracked(|lines| lines.last().map(|(id, _)| *id)) else {
+            return;
+        };
+        remove(id);
+    };
+
+    // Select exactly the line list, so copying doesn't also grab toolbar
+    // labels and the counter.
+    let select_all_lines = move || {
+        let Some(lines_element) = document().get_element_by_id("lines") else {
+            return;
+        };
+        let range = document().create_range().expect("valid call");
+        range
+            .select_node_contents(&lines_element)
+            .expect("valid call");
+        let selection = window()
+            .get_selection()
+            .expect("valid call")
+            .expect("selection exists");
+        selection.remove_all_ranges().expect("valid call");
+        selection.add_range(&range).expect("valid call");
+    };
+
+    let toggle_pause = move || {
+        let now_paused = !paused.get_untracked();
+        paused.set(now_paused);
+        let message = if now_paused {
+            "Capture paused"
+        } else {
+            "Capture resumed"
+        };
+        push_toast(message.to_string(), false);
+    };
+
+    let copy_newest = move || {
+        let Some(id) = lines.with_untracked(|lines| lines.last().map(|(id, _)| *id)) else {
+            return;
+        };
+        copy_line(id);
+        push_toast("Copied line".to_string(), false);
+    };
+
+    let adjust_font_size = move |delta: i32| {
+        let current = font_size.get_untracked().0 as i32;
+        set_font_size.set(FontSize((current + delta).max(1) as u32));
+    };
+
+    // Pinching over the line list scales the text instead of the page.
+    // Each pinch is relative to the font size it started from.
+    let pinch_start = store_value(None::<(f64, u32)>);
+    let on_pinch_start = move |ev: web_sys::TouchEvent| {
+        if let Some(distance) = touch_distance(&ev) {
+            pinch_start.set_value(Some((distance, font_size.get_untracked().0)));
+        }
+    };
+    let on_pinch_move = move |ev: web_sys::TouchEvent| {
+        let Some((start_distance, start_size)) = pinch_start.get_value() else {
+            return;
+        };
+        let Some(distance) = touch_distance(&ev) else {
+            return;
+        };
+        ev.prevent_default();
+        let scaled = (f64::from(start_size) * distance / start_distance).round();
+        set_font_size.set(FontSize(scaled.max(1.0) as u32));
+    };
+    let on_pinch_end = move |ev: web_sys::TouchEvent| {
+        if ev.touches().length() < 2 {
+            pinch_start.set_value(None);
+        }
+    };
+
+    // Ctrl+scroll zooms the text instead of the page. Wheel listeners are
+    // passive by default, which would make `prevent_default` a no-op.
+    let _ = use_event_listener_with_options(
+        window(),
+        ev::wheel,
+        move |ev| {
+            if ev.ctrl_key() {
+                ev.prevent_default();
+                adjust_font_size(if ev.delta_y() < 0.0 { 1 } else { -1 });
+            }
+        },
+        UseEventListenerOptions::default().passive(false),
+    );
+
+    let (bindings, _, _) = use_local_storage::<KeyBindings, JsonCodec>("key-bindings");
+    let cheat_sheet_open = create_rw_signal(false);
+    let diagnostics_open = create_rw_signal(false);
+    let recording = create_rw_signal(None::<Action>);
+    provide_context(RecordingAction(recording));
+
+    let confirm_modal_ref = create_node_ref::<html::Div>();
+    let cheat_modal_ref = create_node_ref::<html::Div>();
+    // Move focus into a dialog once it has rendered.
+    let focus_on_open = move |open: RwSignal<bool>, modal_ref: NodeRef<html::Div>| {
+        create_effect(move |_| {
+            if open.get() {
+                request_animation_frame(move || {
+                    if let Some(modal) = modal_ref.get_untracked() {
+                        modal.focus().expect("focus should not fail");
+                    }
+                });
+            }
+        });
+    };
+    focus_on_open(confirm_clear_open, confirm_modal_ref);
+    focus_on_open(cheat_sheet_open, cheat_modal_ref);
+
+    let _ = use_event_listener(window(), ev::keydown, move |ev| {
+        // Keydown events fired mid-IME-conversion are not real keypresses.
+        if ev.is_composing() {
+            return;
+        }
+        if recording.get_untracked().is_some() {
+            return;
+        }
+        // A real form control (the quick-add field, the secondary pane,
+        // any settings input) owns the keyboard while focused: no cheat
+        // sheet on "?", and no app-level select-all or undo clobbering the
+        // control's own.
+        let typing = document().active_element().is_some_and(|element| {
+            matches!(element.tag_name().as_str(), "INPUT" | "TEXTAREA" | "SELECT")
+        });
+        if typing {
+            return;
+        }
+        if ev.key() == "?" && focused_id.get_untracked().is_none() {
+            ev.prevent_default();
+            if cheat_sheet_open.get_untracked() {
+                cheat_sheet_open.set(false);
+                restore_focus();
+            } else {
+                remember_focus();
+                cheat_sheet_open.set(true);
+            }
+            return;
+        }
+        if ev.key() == "Escape" && cheat_sheet_open.get_untracked() {
+            cheat_sheet_open.set(false);
+            restore_focus();
+            return;
+        }
+        if ev.key() == "Escape" && confirm_clear_open.get_untracked() {
+            confirm_clear_open.set(false);
+            restore_focus();
+            return;
+        }
+        if ev.key() == "Escape" && diagnostics_open.get_untracked() {
+            diagnostics_open.set(false);
+            restore_focus();
+            return;
+        }
+        if let Some(focused) = focused_id.get_untracked() {
+            let element = document()
+                .active_element()
+                .and_then(|el| el.dyn_into::<web_sys::HtmlElement>().ok());
+            if ev.key() == "Escape" {
+                if let Some(element) = element {
+                    element.blur().expect("blur should not fail");
+                }
+                return;
+            }
+            // Undo/redo inside a line first exhausts the browser's native
+            // contenteditable history; once the text is back at its committed
+            // value, fall through to the app-level stack.
+            let action = bindings.with_untracked(|bindings| bindings.action_for(&ev));
+            if let Some(action @ (Action::Undo | Action::Redo)) = action {
+                let committed = lines
+                    .with_untracked(|lines| lines.get(&focused).map(|line| line.text.clone()));
+                let Some(element) = element else {
+                    return;
+                };
+                if committed.is_some_and(|committed| element.inner_text() == committed) {
+                    ev.prevent_default();
+                    element.blur().expect("blur should not fail");
+                    match action {
+                        Action::Undo => undo(),
+                        _ => redo(),
+                    }
+                }
+            }
+            return;
+        }
+        let Some(action) = bindings.with_untracked(|bindings| bindings.action_for(&ev)) else {
+            return;
+        };
+        ev.prevent_default();
+        match action {
+            Action::Undo => undo(),
+            Action::Redo => redo(),
+            Action::Clear => request_clear(),
+            Action::ToggleZen => set_zen.set(!zen.get_untracked()),
+            Action::AdvanceReadMarker => advance_read_marker(),
+            Action::FontSizeUp => adjust_font_size(1),
+            Action::FontSizeDown => adjust_font_size(-1),
+            Action::CopyNewest => copy_newest(),
+            Action::TogglePause => toggle_pause(),
+            Action::AddLine => add_focused_entry(),
+            Action::DeleteNewest => delete_newest(),
+            Action::SelectAllLines => select_all_lines(),
+        }
+    });
+
+    let cheat_sheet_rows = move || {
+        bindings.with(|bindings| {
+            Action::ALL
+                .iter()
+                .map(|action| {
+                    let binding = bindings
+                        .0
+                        .get(action)
+                        .map_or_else(|| "unbound".to_string(), ToString::to_string);
+                    view! {
+                        <div class="shortcut_row">
+                            <span>{action.label()}</span>
+                            <span class="shortcut_key">{binding}</span>
+                        </div>
+                    }
+                })
+                .collect_view()
+        })
+    };
+
+    let undo_disabled = move || read_only.get() || !undo_stack.with(UndoStack::can_undo);
+    let redo_disabled = move || read_only.get() || !undo_stack.with(UndoStack::can_redo);
+
+    view! {
+        <div class="container" role="toolbar" class:auto_hide=auto_hide_toolbar>
+            <button
+                class="container_button"
+                id="clear_button"
+                class:disabled_button=move || read_only.get()
+                title="Clear localStorage"
+                aria-label="Clear all lines"
+                on:click=move |_| request_clear()
+            >
+                <IconView icon=Icon::Delete/>
+            </button>
+            <Show when={move || save_policy.get() != SavePolicy::Immediate}>
+                <button
+                    class="container_button"
+                    id="save_button"
+                    class:dirty=move || dirty.get()
+                    title="Save now"
+                    aria-label="Save log to storage now"
+                    on:click=move |_| save_now()
+                >
+                    <IconView icon=Icon::ContentSave/>
+                </button>
+            </Show>
+            <button
+                class="container_button"
+                class:disabled_button=redo_disabled
+                title="Redo last action"
+                aria-label="Redo last action"
+                on:click=move |_| redo()
+            >
+                <IconView icon=Icon::Redo/>
+            </button>
+            <button
+                class="container_button"
+                class:disabled_button=undo_disabled
+                title="Undo last action"
+                aria-label="Undo last action"
+                on:click=move |_| undo()
+            >
+                <IconView icon=Icon::Undo/>
+            </button>
+            <button
+                class="container_button"
+                title="Add new line"
+                aria-label="Add new line"
+                on:click=move |_| add_focused_entry()
+            >
+                <IconView icon=Icon::Plus/>
+            </button>
+            <button
+                class="container_button"
+                title="Pause capture"
+                aria-label="Pause or resume capture"
+                on:click=move |_| paused.update(|paused| *paused = !*paused)
+            >
+                <IconView icon=Signal::derive(move || {
+                    if paused.get() { Icon::Play } else { Icon::Pause }
+                })/>
+            </button>
+            <button
+                class="container_button"
+                title="Toggle fullscreen"
+                aria-label="Toggle fullscreen"
+                on:click=move |_| toggle_fullscreen()
+            >
+                <IconView icon=Signal::derive(move || {
+                    if fullscreen.get() { Icon::FullscreenExit } else { Icon::Fullscreen }
+                })/>
+            </button>
+            <button
+                class="container_button"
+                title="Toggle zen mode (Alt+Z)"
+                aria-label="Toggle zen mode"
+                on:click=move |_| set_zen.set(!zen.get_untracked())
+            >
+                <IconView icon=Icon::EyeOff/>
+            </button>
+            <button
+                class="container_button"
+                id="read_only_button"
+                class:locked=move || read_only.get()
+                title="Read-only lock"
+                aria-label="Toggle read-only lock"
+                on:click=move |_| set_read_only.set(!read_only.get_untracked())
+            >
+                <IconView icon=Icon::Lock/>
+            </button>
+            <Show when=move || !sync_url.get().is_empty()>
+                <button
+                    class="container_button"
+                    title="Push to sync server"
+                    aria-label="Push to sync server"
+                    on:click=move |_| sync_push()
+                >
+                    <IconView icon=Icon::CloudUpload/>
+                </button>
+                <button
+                    class="container_button"
+                    title="Pull from sync server"
+                    aria-label="Pull from sync server"
+                    on:click=move |_| sync_pull()
+                >
+                    <IconView icon=Icon::CloudDownload/>
+                </button>
+            </Show>
+            <button
+                class="container_button"
+                title="Share selection as link"
+                aria-label="Share selection as link"
+                on:click=move |_| share_selection()
+            >
+                <IconView icon=Icon::Share/>
+            </button>
+            <button
+                class="container_button"
+                title="Diagnostics"
+                aria-label="Diagnostics"
+                on:click=move |_| {
+                    remember_focus();
+                    diagnostics_open.set(true);
+                }
+            >
+                <IconView icon=Icon::Console/>
+            </button>
+            <button
+                class="container_button"
+                title="Download as JSON"
+                aria-label="Download as JSON"
+                on:click=move |_| download()
+            >
+                <IconView icon=Icon::Download/>
+            </button>
+            <div id="counter" title="No. of lines">
+                {move || lines.with(|lines| lines.len())}
+            </div>
+        </div>
+        // Announces each captured line to assistive tech without moving focus.
+        <div class="visually_hidden" aria-live="polite">
+            {move || {
+                newest_id
+                    .get()
+                    .and_then(|id| lines.with(|lines| lines.get(&id).map(|line| line.text.clone())))
+            }}
+        </div>
+        <Show when={
+            move || !(session_title.get().is_empty() && session_notes.get().is_empty())
+        }>
+            <div
+                class="session_header"
+                style=move || {
+                    let color = session_color.get();
+                    (!color.is_empty()).then(|| format!("border-left-color: {color}"))
+                }
+            >
+                <span class="session_title">{session_title}</span>
+                <span class="session_notes">{session_notes}</span>
+            </div>
+        </Show>
+        <div
+            id="lines"
+            class:line_numbers=line_numbers
+            on:touchstart=on_pinch_start
+            on:touchmove=on_pinch_move
+            on:touchend=on_pinch_end
+            style=move || {
+                format!(
+                    "font-size: {}px; text-align: {}; direction: {}",
+                    font_size.get().0,
+                    text_align.get().as_css(),
+                    direction.get().as_css(),
+                )
+            }
+        >
+            <Show when=move || paragraph_view.get()>
+                <For
+                    each=move || paragraphs.get()
+                    key=|(id, text)| (*id, text.clone())
+                    children=move |(_, text)| view! { <p class="paragraph_block">{text}</p> }
+                />
+            </Show>
+            <Show when=move || !paragraph_view.get()>
+                // Keyed on the id alone: a row is built once and later edits flow
+                // through its per-line memos in place, instead of tearing the
+                // node down and re-creating it on every version bump.
+                <For
+                    each=move || {
+                        let filter = speaker_filter.get();
+                        lines
+                            .with(|lines| {
+                                let skip = match overlay {
+                                    Some(count) => lines.len().saturating_sub(count),
+                                    None => 0,
+                                };
+                                let visible = lines
+                                    .iter()
+                                    .skip(skip)
+                                    .filter(|(_, line)| {
+                                        filter.is_empty()
+                                            || line.speaker.as_deref() == Some(filter.as_str())
+                                    });
+                                if !collapse_repeats.get() {
+                                    return visible.map(|(&id, _)| id).collect::<Vec<_>>();
+                                }
+                                expanded_runs
+                                    .with(|expanded| {
+                                        let mut out = Vec::new();
+                                        let mut run = None::<(bool, &str)>;
+                                        for (&id, line) in visible {
+                                            if let Some((head_expanded, text)) = run {
+                                                if text == line.text {
+                                                    if head_expanded {
+                                                        out.push(id);
+                                                    }
+                                                    continue;
+                                                }
+                                            }
+                                            run = Some((expanded.contains(&id), &line.text));
+                                            out.push(id);
+                                        }
+                                        out
+                                    })
+                            })
+                    }
+                    key=|id| *id
+                    children=move |id| {
+                        // Every mutation bumps the line's version, so the rows
+                        // track that alone: a change elsewhere in the log costs
+                        // each row a `usize` comparison, and a line's text is
+                        // only cloned out of the map when its own version moves.
+                        let version = create_memo(move |_| {
+                            lines.with(|lines| lines.get(&id).map(|line| line.version))
+                        });
+                        let text = create_memo(move |_| {
+                            version.track();
+                            lines
+                                .with_untracked(|lines| {
+                                    lines.get(&id).map(|line| line.text.clone()).unwrap_or_default()
+                                })
+                        });
+                        let tagged = create_memo(move |_| {
+                            version.track();
+                            lines
+                                .with_untracked(|lines| {
+                                    lines
+                                        .get(&id)
+                                        .is_some_and(|line| {
+                                            line.tags
+                                                .iter()
+                                                .any(|tag| {
+                                                    *tag
+                                                        == or_default(
+                                                            anki_export_tag.get_untracked(),
+                                                            ANKI_EXPORT_DEFAULT_TAG,
+                                                        )
+                                                })
+                                        })
+                                })
+                        });
+                        let speaker = create_memo(move |_| {
+                            version.track();
+                            lines
+                                .with_untracked(|lines| {
+                                    lines.get(&id).and_then(|line| line.speaker.clone())
+                                })
+                        });
+                        let flagged = create_memo(move |_| {
+                            version.track();
+                            lines
+                                .with_untracked(|lines| {
+                                    lines.get(&id).is_some_and(|line| {
+                                        line.tags.iter().any(|tag| tag == LANGUAGE_FLAG_TAG)
+                                    })
+                                })
+                        });
+                        let locked = create_memo(move |_| {
+                            version.track();
+                            lines
+                                .with_untracked(|lines| {
+                                    lines.get(&id).is_some_and(|line| line.locked)
+                                })
+                        });
+                        // How many copies this row stands for; run heads only.
+                        // Walks the same skipped-and-filtered sequence the
+                        // `<For>` collapses, so the count matches the rows on
+                        // screen when a speaker filter hides copies in between.
+                        // Tracks the whole map, but short-circuits to a toggle
+                        // read while collapsing is off.
+                        let repeats = create_memo(move |_| {
+                            if !collapse_repeats.get() {
+                                return 1;
+                            }
+                            let filter = speaker_filter.get();
+                            lines.with(|lines| {
+                                let skip = match overlay {
+                                    Some(count) => lines.len().saturating_sub(count),
+                                    None => 0,
+                                };
+                                let mut visible =
+                                    lines.iter().skip(skip).filter(|(_, line)| {
+                                        filter.is_empty()
+                                            || line.speaker.as_deref() == Some(filter.as_str())
+                                    });
+                                let mut prev = None::<&str>;
+                                let text = loop {
+                                    match visible.next() {
+                                        Some((&line_id, line)) if line_id == id => {
+                                            if prev == Some(line.text.as_str()) {
+                                                return 1;
+                                            }
+                                            break line.text.as_str();
+                                        }
+                                        Some((_, line)) => prev = Some(line.text.as_str()),
+                                        None => return 1,
+                                    }
+                                };
+                                visible.take_while(|(_, line)| line.text == text).count() + 1
+                            })
+                        });
+                        view! {
+                            <LineView
+                                id
+                                text
+                                tagged
+                                speaker
+                                flagged
+                                locked
+                                toggle_lock
+                                repeats
+                                toggle_expand
+                                toggle_tag
+                                focused_id
+                                selection
+                                select
+                                pending_focus
+                                newest_id
+                                remove
+                                duplicate_line
+                                set_text
+                                send_to_anki
+                                create_anki_note
+                                copy_line
+                                jpdb_enabled
+                                send_to_jpdb
+                                speak_line
+                            />
+                        }
+                    }
+                />
+            </Show>
+        </div>
+        <Show when=move || split_view.get()>
+            <div id="secondary_pane">
+                <For
+                    each=move || {
+                        secondary_lines.with(|lines| lines.keys().copied().collect::<Vec<_>>())
+                    }
+                    key=|id| *id
+                    children=move |id| {
+                        let text = create_memo(move |_| {
+                            secondary_lines
+                                .with(|lines| {
+                                    lines
+                                        .get(&id)
+                                        .map(|line| line.text.clone())
+                                        .unwrap_or_default()
+                                })
+                        });
+                        view! {
+                            <div class="line_box">
+                                <span class="line_text">{text}</span>
+                                <button
+                                    class="line_button"
+                                    title="Remove line"
+                                    aria-label="Remove line"
+                                    on:click=move |_| {
+                                        set_secondary_lines.update(|lines| {
+                                            lines.shift_remove(&id);
+                                        });
+                                    }
+                                >
+                                    <IconView icon=Icon::Close/>
+                                </button>
+                            </div>
+                        }
+                    }
+                />
+                <input
+                    id="secondary_add"
+                    type="text"
+                    placeholder="Add to secondary log"
+                    on:keydown=move |ev: web_sys::KeyboardEvent| {
+                        if ev.key() != "Enter" || ev.is_composing() {
+                            return;
+                        }
+                        let input = event_target::<web_sys::HtmlInputElement>(&ev);
+                        let text = input.value();
+                        if text.is_empty() {
+                            return;
+                        }
+                        input.set_value("");
+                        add_secondary(vec![text]);
+                    }
+                />
+            </div>
+        </Show>
+        <Show when=move || confirm_clear_open.get()>
+            <div
+                class="modal_backdrop"
+                on:click=move |_| {
+                    confirm_clear_open.set(false);
+                    restore_focus();
+                }
+                on:keydown=move |ev| {
+                    if let Some(modal) = confirm_modal_ref.get_untracked() {
+                        trap_focus(&ev, &modal);
+                    }
+                }
+            >
+                <div
+                    class="modal"
+                    role="dialog"
+                    tabindex="-1"
+                    node_ref=confirm_modal_ref
+                    on:click=|ev| ev.stop_propagation()
+                >
+                    <div class="modal_message">
+                        {move || format!("Clear all {} lines?", lines.with(|lines| lines.len()))}
+                    </div>
+                    <div class="modal_buttons">
+                        <button on:click=move |_| {
+                            confirm_clear_open.set(false);
+                            restore_focus();
+                            clear();
+                        }>"Clear"</button>
+                        <button on:click=move |_| {
+                            confirm_clear_open.set(false);
+                            restore_focus();
+                        }>"Cancel"</button>
+                    </div>
+                </div>
+            </div>
+        </Show>
+        <Show when=move || recovery_open.get()>
+            <div class="modal_backdrop">
+                <div class="modal" role="dialog">
+                    <div class="modal_message">
+                        "The saved log could not be read, so it was set aside instead of loaded. "
+                        "Download it to recover the text, or discard it."
+                    </div>
+                    <div class="modal_buttons">
+                        <button on:click=move |_| {
+                            if let Some(raw) = corrupted_lines.get_value() {
+                                download_text("texthooker-corrupted.json", &raw);
+                            }
+                            recovery_open.set(false);
+                        }>"Download raw data"</button>
+                        <button on:click=move |_| {
+                            if let Ok(Some(storage)) = window().local_storage() {
+                                let _ = storage.remove_item(CORRUPTED_LINES_KEY);
+                            }
+                            corrupted_lines.set_value(None);
+                            recovery_open.set(false);
+                        }>"Discard"</button>
+                        <button on:click=move |_| {
+                            recovery_open.set(false);
+                        }>"Keep for later"</button>
+                    </div>
+                </div>
+            </div>
+        </Show>
+        <Show when=move || flooded.get()>
+            <div class="modal_backdrop">
+                <div class="modal" role="dialog">
+                    <div class="modal_message">
+                        {move || format!(
+                            "A source sent over {FLOOD_THRESHOLD} lines within a second, so \
+                             ingestion is paused. {} line(s) are being held.",
+                            flood_held.with(|held| held.len()),
+                        )}
+                    </div>
+                    <div class="modal_buttons">
+                        <button on:click=move |_| flood_resume()>"Resume"</button>
+                        <button on:click=move |_| flood_discard()>"Discard burst"</button>
+                    </div>
+                </div>
+            </div>
+        </Show>
+        <Show when=move || cheat_sheet_open.get()>
+            <div
+                class="modal_backdrop"
+                on:click=move |_| {
+                    cheat_sheet_open.set(false);
+                    restore_focus();
+                }
+                on:keydown=move |ev| {
+                    if let Some(modal) = cheat_modal_ref.get_untracked() {
+                        trap_focus(&ev, &modal);
+                    }
+                }
+            >
+                <div
+                    class="modal cheat_sheet"
+                    role="dialog"
+                    tabindex="-1"
+                    node_ref=cheat_modal_ref
+                    on:click=|ev| ev.stop_propagation()
+                >
+                    <div class="settings_section_title">"Keyboard shortcuts"</div>
+                    {cheat_sheet_rows}
+                </div>
+            </div>
+        </Show>
+        <Show when=move || diagnostics_open.get()>
+            <div
+                class="modal_backdrop"
+                on:click=move |_| {
+                    diagnostics_open.set(false);
+                    restore_focus();
+                }
+            >
+                <div
+                    class="modal diagnostics"
+                    role="dialog"
+                    on:click=|ev| ev.stop_propagation()
+                >
+                    <div class="settings_section_title">"Diagnostics"</div>
+                    <Show
+                        when={move || diagnostics.with(|events| !events.is_empty())}
+                        fallback=|| view! { <div class="diag_row">"No events yet."</div> }
+                    >
+                        <For
+                            each=move || {
+                                diagnostics.get().into_iter().rev().collect::<Vec<_>>()
+                            }
+                            key=|event| event.id
+                            children=|event| {
+                                view! {
+                                    <div class="diag_row">
+                                        <span class="diag_time">
+                                            {format_timestamp(event.at)}
+                                        </span>
+                                        {event.message}
+                                    </div>
+                                }
+                            }
+                        />
+                    </Show>
+                </div>
+            </div>
+        </Show>
+        <input
+            id="quick_add"
+            type="text"
+            placeholder="Add line"
+            on:keydown=quick_add
+        />
+        <div id="toasts">
+            <For
+                each=move || toasts.get()
+                key=|toast| toast.id
+                children=move |toast| {
+                    let toast_id = toast.id;
+                    view! {
+                        <div class="toast">
+                            <span>{toast.message}</span>
+                            {toast
+                                .undo_len
+                                .map(|len| {
+                                    view! {
+                                        <button
+                                            class="toast_undo"
+                                            on:click=move |_| {
+                                                if undo_stack.with_untracked(UndoStack::undo_depth) == len {
+                                                    undo();
+                                                }
+                                                toasts
+                                                    .update(|toasts| {
+                                                        toasts.retain(|toast| toast.id != toast_id)
+                                                    });
+                                            }
+                                        >
+                                            "Undo"
+                                        </button>
+                                    }
+                                })}
+                        </div>
+                    }
+                }
+            />
+        </div>
+        {dictionary_popup}
+        <UpdateBanner/>
+        <SettingsPanel/>
+    }
+}
+
+/// Assigns a speaker its line-tint color by hashing the name into a fixed
+/// palette: stable across sessions with nothing to store, at the cost of
+/// collisions once a script has more speakers than palette entries.
+fn speaker_color(name: &str) -> &'static str {
+    const PALETTE: [&str; 8] = [
+        "#61afef", "#98c379", "#e5c07b", "#e06c75", "#c678dd", "#56b6c2", "#d19a66", "#abb2bf",
+    ];
+    let hash = name
+        .bytes()
+        .fold(0_usize, |hash, byte| hash.wrapping_mul(31).wrapping_add(byte.into()));
+    PALETTE[hash % PALETTE.len()]
+}
+
+/// Rough silent-reading speed used for the per-line time estimate. Actual
+/// speeds vary wildly by reader and material; this is only meant to rank
+/// lines against each other.
+const READING_CHARS_PER_MIN: u32 = 400;
+
+/// A single line of the log: the text plus its hover-revealed edit and remove
+/// buttons.
+#[component]
+fn LineView(
+    id: usize,
+    #[prop(into)] text: Signal<String>,
+    #[prop(into)] tagged: Signal<bool>,
+    #[prop(into)] speaker: Signal<Option<String>>,
+    #[prop(into)] flagged: Signal<bool>,
+    #[prop(into)] locked: Signal<bool>,
+    #[prop(into)] toggle_lock: Callback<usize>,
+    #[prop(into)] repeats: Signal<usize>,
+    #[prop(into)] toggle_expand: Callback<usize>,
+    #[prop(into)] toggle_tag: Callback<usize>,
+    focused_id: RwSignal<Option<usize>>,
+    selection: RwSignal<HashSet<usize>>,
+    #[prop(into)] select: Callback<(usize, bool)>,
+    pending_focus: RwSignal<Option<usize>>,
+    newest_id: RwSignal<Option<usize>>,
+    #[prop(into)] remove: Callback<usize>,
+    #[prop(into)] duplicate_line: Callback<usize>,
+    #[prop(into)] set_text: Callback<(usize, String)>,
+    #[prop(into)] send_to_anki: Callback<usize>,
+    #[prop(into)] create_anki_note: Callback<usize>,
+    #[prop(into)] copy_line: Callback<usize>,
+    #[prop(into)] jpdb_enabled: Signal<bool>,
+    #[prop(into)] send_to_jpdb: Callback<usize>,
+    #[prop(into)] speak_line: Callback<usize>,
+) -> impl IntoView {
+    let editing = create_rw_signal(false);
+    let text_ref = create_node_ref::<html::Span>();
+    let (char_count, _, _) = use_local_storage::<bool, JsonCodec>("line-char-count");
+    let line_meta = move || {
+        let count = text.with(|text| {
+            text.chars().filter(|c| !c.is_whitespace()).count()
+        });
+        let seconds =
+            (count as f64 * 60.0 / f64::from(READING_CHARS_PER_MIN)).ceil() as u64;
+        format!("{count} chars, ~{seconds}s")
+    };
+    let (highlight, _, _) = use_local_storage::<HighlightStyle, JsonCodec>("highlight-newest");
+    let (strip_ruby, _, _) = use_local_storage::<bool, JsonCodec>("strip-ruby");
+    // Browser spellcheck underlines every Japanese word in red, and mobile
+    // keyboards capitalize and "correct" edits into garbage, so all three
+    // editing aids stay off unless asked for.
+    let (edit_spellcheck, _, _) = use_local_storage::<bool, JsonCodec>("edit-spellcheck");
+    // The global read-only lock greys the destructive buttons and makes
+    // edit mode unreachable; the mutation closures themselves are guarded
+    // in `MainPage`, which also covers the keyboard shortcuts.
+    let (read_only, _, _) = use_local_storage::<bool, JsonCodec>("read-only");
+    let (click_to_copy, _, _) = use_local_storage::<bool, JsonCodec>("click-to-copy");
+    let (speaker_colors, _, _) = use_local_storage::<bool, JsonCodec>("speaker-colors");
+    // The occasional giant block (credits roll, whole-scene dump) is
+    // clamped to the first N characters with an expand control; zero turns
+    // clamping off. Edit mode always shows — and commits — the full text.
+    let (clamp_chars, _, _) = use_local_storage::<u32, JsonCodec>("clamp-chars");
+    let expanded = create_rw_signal(false);
+    let clampable = create_memo(move |_| {
+        let limit = clamp_chars.get() as usize;
+        limit > 0 && text.with(|text| text.chars().count() > limit)
+    });
+    let clamped = move || clampable.get() && !expanded.get() && !editing.get();
+    let (read_marker, set_read_marker, _) =
+        use_local_storage::<Option<usize>, JsonCodec>("read-marker");
+    let newest = move || newest_id.get() == Some(id);
+
+    // Known-word highlighting: the imported list is held as a set, with the
+    // longest word length bounding the greedy matcher.
+    let (highlight_unknown, _, _) = use_local_storage::<bool, JsonCodec>("highlight-unknown");
+    let (known_words, _, _) = use_local_storage::<Vec<String>, JsonCodec>("known-words");
+    let known_set = create_memo(move |_| {
+        known_words.with(|words| {
+            let max_len = words.iter().map(|word| word.chars().count()).max();
+            (
+                words.iter().cloned().collect::<HashSet<String>>(),
+                max_len.unwrap_or(0),
+            )
+        })
+    });
+    let render_plain = move |text: String| {
+        if !highlight_unknown.get() {
+            return text.into_view();
+        }
+        known_set.with(|(known, max_len)| {
+            if known.is_empty() {
+                return text.into_view();
+            }
+            split_known(&text, known, *max_len)
+                .into_iter()
+                .map(|(run, known)| {
+                    if known {
+                        run.into_view()
+                    } else {
+                        view! { <span class="unknown_word">{run}</span> }.into_view()
+                    }
+                })
+                .collect_view()
+                .into_view()
+        })
+    };
+
+    let rendered = move || {
+        // Edit mode shows the raw stored text, ruby notation and all:
+        // rendering real <ruby> elements into the contenteditable span
+        // would flatten base and reading together when the commit reads
+        // `inner_text`, silently rewriting the line on a mere blur.
+        if editing.get() {
+            return text.get().into_view();
+        }
+        let source = if clamped() {
+            text.with(|text| {
+                let mut short: String = text.chars().take(clamp_chars.get() as usize).collect();
+                short.push('…');
+                short
+            })
+        } else {
+            text.get()
+        };
+        parse_ruby(&source)
+            .into_iter()
+            .map(|segment| match segment {
+                TextSegment::Plain(text) => render_plain(text),
+                TextSegment::Ruby { base, reading } => {
+                    if strip_ruby.get() {
+                        render_plain(base)
+                    } else {
+                        view! { <ruby>{base}<rt>{reading}</rt></ruby> }.into_view()
+                    }
+                }
+            })
+            .collect_view()
+    };
+
+    let focus = move || {
+        if locked.get_untracked() || read_only.get_untracked() {
+            return;
+        }
+        editing.set(true);
+        focused_id.set(Some(id));
+        let span = text_ref.get_untracked().expect("span should exist");
+        let _ = span.focus();
+    };
+
+    // A line created through `add_focused_entry` starts out in edit mode.
+    create_effect(move |_| {
+        if pending_focus.get() == Some(id) {
+            pending_focus.set(None);
+            focus();
+        }
+    });
+
+    // Long-pressing a line enters edit mode; the `pressing` tint shows the
+    // press is registering. Any movement or release cancels the timer.
+    let pressing = create_rw_signal(false);
+    let press_timer = store_value(None::<leptos_dom::helpers::TimeoutHandle>);
+    let cancel_press = move || {
+        pressing.set(false);
+        if let Some(handle) = press_timer.try_update_value(Option::take).flatten() {
+            handle.clear();
+        }
+    };
+
+    // Swipe left to delete, swipe right to copy; mostly-vertical moves are
+    // scrolling and ignored.
+    let touch_start = store_value(None::<(i32, i32)>);
+    let on_touch_start = move |ev: web_sys::TouchEvent| {
+        let Some(touch) = ev.changed_touches().get(0) else {
+            return;
+        };
+        touch_start.set_value(Some((touch.client_x(), touch.client_y())));
+        pressing.set(true);
+        let handle = set_timeout_with_handle(
+            move || {
+                pressing.set(false);
+                focus();
+            },
+            Duration::from_millis(500),
+        )
+        .expect("timeout should be set");
+        press_timer.set_value(Some(handle));
+    };
+    let on_touch_end = move |ev: web_sys::TouchEvent| {
+        cancel_press();
+        let Some((start_x, start_y)) = touch_start.get_value() else {
+            return;
+        };
+        touch_start.set_value(None);
+        let Some(touch) = ev.changed_touches().get(0) else {
+            return;
+        };
+        let dx = touch.client_x() - start_x;
+        let dy = touch.client_y() - start_y;
+        if dx.abs() < 50 || dx.abs() < dy.abs() * 2 {
+            return;
+        }
+        if dx < 0 {
+            remove.call(id);
+        } else {
+            copy_line.call(id);
+        }
+    };
+
+    // In click-to-copy mode a plain click on the text copies the whole line;
+    // the flash confirms the copy happened.
+    let copy_flash = create_rw_signal(false);
+    let on_click_text = move |ev: web_sys::MouseEvent| {
+        if editing.get_untracked() {
+            return;
+        }
+        if ev.shift_key() {
+            ev.prevent_default();
+            select.call((id, true));
+            return;
+        }
+        let modifier = if is_mac() { ev.meta_key() } else { ev.ctrl_key() };
+        if modifier {
+            select.call((id, false));
+            return;
+        }
+        if !click_to_copy.get_untracked() {
+            return;
+        }
+        copy_line.call(id);
+        copy_flash.set(true);
+        set_timeout(move || copy_flash.set(false), Duration::from_millis(600));
+    };
+
+    // Rich-source pastes inject HTML that `inner_text` then reads back
+    // inconsistently; insert the plain text flavour instead.
+    let on_paste = move |ev: web_sys::Event| {
+        if !editing.get_untracked() {
+            return;
+        }
+        ev.prevent_default();
+        let ev = ev.unchecked_into::<web_sys::ClipboardEvent>();
+        let Some(text) = ev
+            .clipboard_data()
+            .and_then(|data| data.get_data("text/plain").ok())
+        else {
+            return;
+        };
+        document()
+            .unchecked_into::<web_sys::HtmlDocument>()
+            .exec_command_with_show_ui_and_value("insertText", false, &text)
+            .expect("insertText should not fail");
+    };
+
+    let commit = move |_| {
+        editing.set(false);
+        focused_id.set(None);
+        let span = text_ref.get_untracked().expect("span should exist");
+        let edited = span.inner_text();
+        if text.with_untracked(|text| edited != *text) {
+            set_text.call((id, edited));
+        }
+    };
+
+    // The mining buttons are compiled out of builds without the `anki`
+    // feature; the props still arrive (as stubs) so the `MainPage` call
+    // site stays uniform.
+    #[cfg(feature = "anki")]
+    let mining_buttons = move || view! {
+        <button
+            class="line_button"
+            title="Send to Anki"
+            aria-label="Send to Anki"
+            on:click=move |_| send_to_anki.call(id)
+        >
+            <IconView icon=Icon::Send/>
+        </button>
+        <button
+            class="line_button"
+            title="Create Anki card"
+            aria-label="Create Anki card"
+            on:click=move |_| create_anki_note.call(id)
+        >
+            <IconView icon=Icon::PlusBox/>
+        </button>
+        <Show when=move || jpdb_enabled.get()>
+            <button
+                class="line_button"
+                title="Send to JPDB"
+                aria-label="Send to JPDB"
+                on:click=move |_| send_to_jpdb.call(id)
+            >
+                <IconView icon=Icon::Book/>
+            </button>
+        </Show>
+    };
+    #[cfg(not(feature = "anki"))]
+    let mining_buttons = move || {
+        let _ = (send_to_anki, create_anki_note, jpdb_enabled, send_to_jpdb);
+    };
+
+    view! {
+        <div
+            class="line_box"
+            class:speaker_tinted=move || {
+                speaker_colors.get() && speaker.with(Option::is_some)
+            }
+            style=move || {
+                (speaker_colors.get())
+                    .then(|| {
+                        speaker
+                            .with(|speaker| {
+                                speaker
+                                    .as_deref()
+                                    .map(|name| {
+                                        format!("border-left-color: {}", speaker_color(name))
+                                    })
+                            })
+                    })
+                    .flatten()
+            }
+            class:language_flagged=move || flagged.get()
+            class:flash_newest=move || newest() && highlight.get() == HighlightStyle::Flash
+            class:tint_newest=move || newest() && highlight.get() == HighlightStyle::Tint
+            class:read_line=move || read_marker.get().is_some_and(|marker| id <= marker)
+            class:read_marker=move || read_marker.get() == Some(id)
+            class:pressing=pressing
+            class:copy_flash=copy_flash
+            class:selected=move || selection.with(|selection| selection.contains(&id))
+            on:touchstart=on_touch_start
+            on:touchmove=move |_| cancel_press()
+            on:touchend=on_touch_end
+        >
+            <span
+                class="line_text"
+                contenteditable=move || editing.get().to_string()
+                spellcheck=move || edit_spellcheck.get().to_string()
+                autocapitalize=move || if edit_spellcheck.get() { "sentences" } else { "off" }
+                autocorrect=move || if edit_spellcheck.get() { "on" } else { "off" }
+                node_ref=text_ref
+                on:focusout=commit
+                on:click=on_click_text
+                on:paste=on_paste
+            >
+                {rendered}
+            </span>
+            <Show when={move || repeats.get() > 1}>
+                <button
+                    class="repeat_badge"
+                    title="Toggle repeated copies"
+                    aria-label="Toggle repeated copies"
+                    on:click=move |_| toggle_expand.call(id)
+                >
+                    {move || format!("\u{d7}{}", repeats.get())}
+                </button>
+            </Show>
+            <Show when=move || clampable.get() && !editing.get()>
+                <button
+                    class="clamp_toggle"
+                    title="Toggle full text"
+                    on:click=move |_| expanded.update(|expanded| *expanded = !*expanded)
+                >
+                    {move || if expanded.get() { "Show less" } else { "Show more" }}
+                </button>
+            </Show>
+            <Show when=move || char_count.get()>
+                <span class="line_meta">{line_meta}</span>
+            </Show>
+            <Show when=move || !locked.get()>
+                <button
+                    class="line_button"
+                    class:disabled_button=move || read_only.get()
+                    title="Edit line"
+                    aria-label="Edit line"
+                    on:click=move |_| focus()
+                >
+                    <IconView icon=Icon::Pencil/>
+                </button>
+            </Show>
+            <button
+                class="line_button"
+                class:disabled_button=move || read_only.get()
+                title="Duplicate line"
+                aria-label="Duplicate line"
+                on:click=move |_| duplicate_line.call(id)
+            >
+                <IconView icon=Icon::ContentCopy/>
+            </button>
+            <button
+                class="line_button"
+                title="Mark read up to here"
+                aria-label="Mark read up to here"
+                on:click=move |_| {
+                    set_read_marker
+                        .set((read_marker.get_untracked() != Some(id)).then_some(id));
+                }
+            >
+                <IconView icon=Icon::Bookmark/>
+            </button>
+            <button
+                class="line_button"
+                title="Play line"
+                aria-label="Play line"
+                on:click=move |_| speak_line.call(id)
+            >
+                <IconView icon=Icon::Play/>
+            </button>
+            <button
+                class="line_button"
+                class:active=move || tagged.get()
+                class:disabled_button=move || read_only.get()
+                title="Tag for mining"
+                aria-label="Tag for mining"
+                on:click=move |_| toggle_tag.call(id)
+            >
+                <IconView icon=Icon::Tag/>
+            </button>
+            {mining_buttons}
+            <button
+                class="line_button"
+                class:active=move || locked.get()
+                title="Lock line"
+                aria-label="Lock line"
+                on:click=move |_| toggle_lock.call(id)
+            >
+                <IconView icon=Icon::Lock/>
+            </button>
+            <Show when=move || !locked.get()>
+                <button
+                    class="line_button"
+                    class:disabled_button=move || read_only.get()
+                    title="Remove line"
+                    aria-label="Remove line"
+                    on:click=move |_| remove.call(id)
+                >
+                    <IconView icon=Icon::Close/>
+                </button>
+            </Show>
+        </div>
+    }
+}
+
+/// The settings corner: a gear toggling a panel of grouped options. The
+/// open/closed state survives reloads.
+#[component]
+fn SettingsPanel() -> impl IntoView {
+    let (open, set_open, _) = use_local_storage::<bool, JsonCodec>("settings-open");
+
+    #[cfg(feature = "dictionary")]
+    let dictionary_toggle =
+        move || view! { <ToggleControl label="Popup dictionary" key="popup-dictionary"/> };
+    #[cfg(not(feature = "dictionary"))]
+    let dictionary_toggle = move || ();
+
+    #[cfg(feature = "ocr")]
+    let ocr_setting = move || {
+        view! {
+            <TextControl
+                label="OCR server URL"
+                key="ocr-url"
+                placeholder="http://127.0.0.1:7331"
+            />
+        }
+    };
+    #[cfg(not(feature = "ocr"))]
+    let ocr_setting = move || ();
+
+    #[cfg(feature = "anki")]
+    let anki_section = move || {
+        view! {
+            <SettingsSection name="Anki">
+                <TextControl
+                    label="AnkiConnect URL"
+                    key="ankiconnect-url"
+                    placeholder=ANKI_CONNECT_DEFAULT_URL
+                />
+                <TextControl
+                    label="Sentence field"
+                    key="anki-sentence-field"
+                    placeholder=ANKI_DEFAULT_SENTENCE_FIELD
+                />
+                <TextControl
+                    label="Deck"
+                    key="anki-deck"
+                    placeholder=ANKI_DEFAULT_DECK
+                />
+                <TextControl
+                    label="Note type"
+                    key="anki-note-type"
+                    placeholder=ANKI_DEFAULT_NOTE_TYPE
+                />
+                <AnkiFieldsControl/>
+                <TextControl
+                    label="JPDB API key"
+                    key="jpdb-api-key"
+                    placeholder="(disabled)"
+                />
+            </SettingsSection>
+        }
+    };
+    #[cfg(not(feature = "anki"))]
+    let anki_section = move || ();
+
+    view! {
+        <div id="settings">
+            <Show when=move || open.get()>
+                <div id="settings_panel">
+                    <SettingsSection name="Display">
+                        <FontControl/>
+                        <AlignmentControl/>
+                        <ToggleControl label="Auto-hide toolbar" key="auto-hide-toolbar"/>
+                        <ToggleControl label="Line numbers" key="line-numbers"/>
+                        <ToggleControl label="Character count on hover" key="line-char-count"/>
+                        <ToggleControl label="Collapse repeated lines" key="collapse-repeats"/>
+                        <ClampCharsControl/>
+                        <ToggleControl label="Paragraph view" key="paragraph-view"/>
+                        <ToggleControl label="Split view" key="split-view"/>
+                        <ToggleControl label="Color lines by speaker" key="speaker-colors"/>
+                        <SpeakerLegendControl/>
+                        <HighlightControl/>
+                        <DensityControl/>
+                        <WrapControl/>
+                    </SettingsSection>
+                    <SettingsSection name="Session">
+                        <TextControl label="Title" key="session-title"/>
+                        <TextControl
+                            label="Cover color"
+                            key="session-color"
+                            placeholder="#61afef"
+                        />
+                        <TextControl label="Notes" key="session-notes"/>
+                    </SettingsSection>
+                    <SettingsSection name="Behavior">
+                        <ToggleControl label="Click line to copy" key="click-to-copy"/>
+                        <ToggleControl
+                            label="No auto-scroll while editing"
+                            key="scroll-lock-editing"
+                        />
+                        <ToggleControl label="Spellcheck while editing" key="edit-spellcheck"/>
+                        <ToggleControl label="Copy with context" key="copy-with-context"/>
+                        <ToggleControl label="Keep screen awake" key="wake-lock"/>
+                        {dictionary_toggle}
+                        <ToggleControl label="Sound when unfocused" key="notify-sound"/>
+                        <ToggleControl
+                            label="Desktop notifications when hidden"
+                            key="desktop-notifications"
+                        />
+                        <ToggleControl label="Mute notifications" key="notifications-muted"/>
+                        <NotificationSoundControl/>
+                        <ToggleControl label="Speak new lines" key="tts-auto"/>
+                        <TextControl label="TTS voice" key="tts-voice"/>
+                        <TtsRateControl/>
+                        <ContextLinesControl/>
+                    </SettingsSection>
+                    <SettingsSection name="Sources">
+                        <TextControl
+                            label="Capture target selector"
+                            key="capture-selector"
+                            placeholder="body"
+                        />
+                        <TextControl
+                            label="WebSocket URL"
+                            key="websocket-url"
+                            placeholder="ws://127.0.0.1:6677"
+                        />
+                        {ocr_setting}
+                        <ToggleControl
+                            label="Ingest into secondary pane"
+                            key="ingest-secondary"
+                        />
+                    </SettingsSection>
+                    <SettingsSection name="Sync">
+                        <TextControl
+                            label="Sync URL"
+                            key="sync-url"
+                            placeholder="http://127.0.0.1:8766/sync"
+                        />
+                        <TextControl
+                            label="Broadcast URL"
+                            key="broadcast-url"
+                            placeholder="ws://127.0.0.1:6678"
+                        />
+                        <TextControl
+                            label="WebDAV backup URL"
+                            key="webdav-url"
+                            placeholder="https://cloud.example/backup.json"
+                        />
+                        <TextControl label="WebDAV username" key="webdav-username"/>
+                        <TextControl label="WebDAV password" key="webdav-password"/>
+                    </SettingsSection>
+                    <SettingsSection name="Stats">
+                        <TextControl
+                            label="Push URL"
+                            key="stats-push-url"
+                            placeholder="ws:// or http://"
+                        />
+                        <StatsIntervalControl/>
+                        <TextControl
+                            label="Push template"
+                            key="stats-push-template"
+                            placeholder=STATS_PUSH_DEFAULT_TEMPLATE
+                        />
+                        <TtsuImportControl/>
+                    </SettingsSection>
+                    {anki_section}
+                    <SettingsSection name="Shortcuts">
+                        <ShortcutsControl/>
+                    </SettingsSection>
+                    <SettingsSection name="Export">
+                        <ExportControl/>
+                    </SettingsSection>
+                    <SettingsSection name="Storage">
+                        <ToggleControl label="Clear without confirmation" key="skip-clear-confirm"/>
+                        <SavePolicyControl/>
+                        <AutosaveIntervalControl/>
+                        <MaxLinesControl/>
+                    </SettingsSection>
+                    <SettingsSection name="Filters">
+                        <ToggleControl label="Strip ruby readings" key="strip-ruby"/>
+                        <ToggleControl label="Highlight unknown words" key="highlight-unknown"/>
+                        <KnownWordsControl/>
+                        <ToggleControl
+                            label="Strip （furigana） after kanji"
+                            key="strip-furigana"
+                        />
+                        <ToggleControl label="Filter recent lookups" key="filter-lookups"/>
+                        <ToggleControl label="Strip speaker markers" key="strip-speaker"/>
+                        <ToggleControl
+                            label="Merge textbox ticks into one line"
+                            key="merge-extensions"
+                        />
+                        <ToggleControl label="Split into sentences" key="split-sentences"/>
+                        <LanguageFilterControl/>
+                        <SpeakerFilterControl/>
+                        <TextControl
+                            label="Speaker brackets"
+                            key="speaker-brackets"
+                            placeholder=SPEAKER_BRACKETS_DEFAULT
+                        />
+                    </SettingsSection>
+                </div>
+            </Show>
+            <button
+                id="settings_toggle"
+                title="Settings"
+                aria-label="Toggle settings panel"
+                aria-expanded=move || open.get().to_string()
+                on:click=move |_| set_open.set(!open.get_untracked())
+            >
+                <IconView icon=Icon::Cog/>
+            </button>
+        </div>
+    }
+}
+
+/// A labelled checkbox bound to a persisted boolean setting.
+#[component]
+fn ToggleControl(label: &'static str, key: &'static str) -> impl IntoView {
+    let (value, set_value, _) = use_local_storage::<bool, JsonCodec>(key);
+
+    view! {
+        <div class="toggle_container">
+            <label>
+                <input
+                    type="checkbox"
+                    prop:checked=move || value.get()
+                    on:change=move |ev| set_value.set(event_target_checked(&ev))
+                />
+                {label}
+            </label>
+        </div>
+    }
+}
+
+/// A titled group of related settings within the panel.
+#[component]
+fn SettingsSection(name: &'static str, children: Children) -> impl IntoView {
+    view! {
+        <div class="settings_section">
+            <div class="settings_section_title">{name}</div>
+            {children()}
+        </div>
+    }
+}
+
+/// A validated numeric input. Values typed into the field are parsed and
+/// clamped to `min..=max` as they arrive — an unparseable draft (empty
+/// field, stray minus) is shown as-is without touching the stored value,
+/// and snaps back to the last good value on blur. The step buttons nudge by
+/// `step` within the same bounds.
+#[component]
+fn NumberControl(
+    label: &'static str,
+    id: &'static str,
+    #[prop(into)] value: Signal<f64>,
+    #[prop(into)] set_value: Callback<f64>,
+    min: f64,
+    max: f64,
+    step: f64,
+) -> impl IntoView {
+    let clamp = move |value: f64| value.clamp(min, max);
+    // Holds the raw field contents while they don't round-trip through the
+    // stored value, so typing is never interrupted.
+    let draft = create_rw_signal(None::<String>);
+    let shown = move || draft.get().unwrap_or_else(|| value.get().to_string());
+    let nudge = move |delta: f64| {
+        draft.set(None);
+        set_value.call(clamp(value.get_untracked() + delta));
+    };
+
+    view! {
+        <div class="number_control">
+            <label for=id>{label}</label>
+            <input
+                id=id
+                type="number"
+                min=min
+                max=max
+                step=step
+                prop:value=shown
+                on:input=move |ev| {
+                    let raw = event_target_value(&ev);
+                    match raw.parse::<f64>() {
+                        Ok(parsed) if parsed.is_finite() => {
+                            let clamped = clamp(parsed);
+                            draft.set((clamped != parsed).then_some(raw));
+                            set_value.call(clamped);
+                        }
+                        _ => draft.set(Some(raw)),
+                    }
+                }
+                on:change=move |_| draft.set(None)
+            />
+            <button
+                class="number_step"
+                title="Decrease"
+                aria-label="Decrease"
+                on:click=move |_| nudge(-step)
+            >
+                "-"
+            </button>
+            <button
+                class="number_step"
+                title="Increase"
+                aria-label="Increase"
+                on:click=move |_| nudge(step)
+            >
+                "+"
+            </button>
+        </div>
+    }
+}
+
+/// The font size input in the settings corner.
+#[component]
+fn FontControl() -> impl IntoView {
+    let (font_size, set_font_size, _) = use_local_storage::<FontSize, JsonCodec>("font-size");
+
+    view! {
+        <NumberControl
+            label="Font Size"
+            id="font-size-input"
+            value=Signal::derive(move || f64::from(font_size.get().0))
+            set_value=move |value: f64| set_font_size.set(FontSize(value as u32))
+            min=1.0
+            max=256.0
+            step=1.0
+        />
+    }
+}
+
+/// A free-text setting persisted under `key`; an empty value means "use the
+/// built-in default" shown as the placeholder.
+#[component]
+fn TextControl(
+    label: &'static str,
+    key: &'static str,
+    #[prop(optional)] placeholder: &'static str,
+) -> impl IntoView {
+    let (value, set_value, _) = use_local_storage::<String, JsonCodec>(key);
+
+    view! {
+        <div class="text_control">
+            <label for=key>{label}</label>
+            <input
+                id=key
+                type="text"
+                placeholder=placeholder
+                prop:value=value
+                on:change=move |ev| set_value.set(event_target_value(&ev))
+            />
+        </div>
+    }
+}
+
+/// The surrounding-lines count used by context copies and the Anki context
+/// mapping.
+#[component]
+fn ContextLinesControl() -> impl IntoView {
+    let (context_lines, set_context_lines, _) = use_local_storage::<u32, JsonCodec>("context-lines");
+
+    view! {
+        <NumberControl
+            label="Context lines"
+            id="context-lines-input"
+            value=Signal::derive(move || f64::from(context_lines.get()))
+            set_value=move |value: f64| set_context_lines.set(value as u32)
+            min=0.0
+            max=50.0
+            step=1.0
+        />
+    }
+}
+
+/// Dropdown for the save policy: when the working line map is written to
+/// localStorage. Changing it away from immediate enables the toolbar's
+/// save button and the dirty indicator on it.
+#[component]
+fn SavePolicyControl() -> impl IntoView {
+    let (policy, set_policy, _) = use_local_storage::<SavePolicy, JsonCodec>("save-policy");
+
+    view! {
+        <div class="select_control">
+            <label for="save-policy-input">"Write log"</label>
+            <select
+                id="save-policy-input"
+                on:change=move |ev| {
+                    set_policy.set(match event_target_value(&ev).as_str() {
+                        "interval" => SavePolicy::Interval,
+                        "manual" => SavePolicy::Manual,
+                        _ => SavePolicy::Immediate,
+                    });
+                }
+                prop:value=move || match policy.get() {
+                    SavePolicy::Immediate => "immediate",
+                    SavePolicy::Interval => "interval",
+                    SavePolicy::Manual => "manual",
+                }
+            >
+                <option value="immediate">"On every change"</option>
+                <option value="interval">"On an interval"</option>
+                <option value="manual">"Manually"</option>
+            </select>
+        </div>
+    }
+}
+
+/// How often interval autosave flushes, in seconds; zero falls back to the
+/// default interval. Takes effect on reload.
+#[component]
+fn AutosaveIntervalControl() -> impl IntoView {
+    let (interval, set_interval, _) = use_local_storage::<u32, JsonCodec>("autosave-interval");
+
+    view! {
+        <NumberControl
+            label="Autosave interval (s)"
+            id="autosave-interval-input"
+            value=Signal::derive(move || f64::from(interval.get()))
+            set_value=move |value: f64| set_interval.set(value as u32)
+            min=0.0
+            max=3600.0
+            step=1.0
+        />
+    }
+}
+
+/// How many characters a line shows before it is clamped behind an expand
+/// control; zero shows everything.
+#[component]
+fn ClampCharsControl() -> impl IntoView {
+    let (clamp_chars, set_clamp_chars, _) = use_local_storage::<u32, JsonCodec>("clamp-chars");
+
+    view! {
+        <NumberControl
+            label="Clamp lines over (chars)"
+            id="clamp-chars-input"
+            value=Signal::derive(move || f64::from(clamp_chars.get()))
+            set_value=move |value: f64| set_clamp_chars.set(value as u32)
+            min=0.0
+            max=10_000.0
+            step=10.0
+        />
+    }
+}
+
+/// The rolling cap on live line count; overflow moves to the archive
+/// store. Zero disables the cap.
+#[component]
+fn MaxLinesControl() -> impl IntoView {
+    let (max_active_lines, set_max_active_lines, _) =
+        use_local_storage::<u32, JsonCodec>("max-active-lines");
+
+    view! {
+        <NumberControl
+            label="Max active lines"
+            id="max-active-lines-input"
+            value=Signal::derive(move || f64::from(max_active_lines.get()))
+            set_value=move |value: f64| set_max_active_lines.set(value as u32)
+            min=0.0
+            max=100_000.0
+            step=100.0
+        />
+    }
+}
+
+/// Download buttons for the non-JSON log exports.
+#[component]
+fn ExportControl() -> impl IntoView {
+    let LiveLines(lines) = expect_context();
+    let (timestamps, _, _) = use_local_storage::<bool, JsonCodec>("export-timestamps");
+    let (font_size, _, _) = use_local_storage::<FontSize, JsonCodec>("font-size");
+    let (text_align, _, _) = use_local_storage::<TextAlign, JsonCodec>("text-align");
+    let (separator, set_separator, _) =
+        use_local_storage::<ExportSeparator, JsonCodec>("export-separator");
+    let (custom_separator, _, _) =
+        use_local_storage::<String, JsonCodec>("export-separator-custom");
+    let (crlf, _, _) = use_local_storage::<bool, JsonCodec>("export-crlf");
+    let (anki_export_tag, _, _) = use_local_storage::<String, JsonCodec>("anki-export-tag");
+    let (session_title, _, _) = use_local_storage::<String, JsonCodec>("session-title");
+    let (session_notes, _, _) = use_local_storage::<String, JsonCodec>("session-notes");
+    let SelectedLines(selection) = expect_context();
+
+    let separator_string = move || match separator.get_untracked() {
+        ExportSeparator::Newline => "\n".to_string(),
+        ExportSeparator::BlankLine => "\n\n".to_string(),
+        ExportSeparator::Custom => custom_separator.get_untracked(),
+    };
+
+    // The whole script (or just the selection, when one exists) as a single
+    // separator-joined string on the clipboard, for external analysis
+    // tools.
+    let copy_joined = move |_| {
+        let selected = selection.get_untracked();
+        let joined = lines.with_untracked(|lines| {
+            lines
+                .iter()
+                .filter(|(id, _)| selected.is_empty() || selected.contains(id))
+                .map(|(_, line)| line.text.as_str())
+                .collect::<Vec<_>>()
+                .join(&separator_string())
+        });
+        clipboard_write_text(&joined);
+    };
+
+    view! {
+        <ToggleControl label="Include timestamps" key="export-timestamps"/>
+        <div id="export-separator-container">
+            <label for="export-separator-input">"Text separator"</label>
+            <select
+                id="export-separator-input"
+                on:change=move |ev| {
+                    set_separator.set(match event_target_value(&ev).as_str() {
+                        "blank_line" => ExportSeparator::BlankLine,
+                        "custom" => ExportSeparator::Custom,
+                        _ => ExportSeparator::Newline,
+                    });
+                }
+                prop:value=move || {
+                    match separator.get() {
+                        ExportSeparator::Newline => "newline",
+                        ExportSeparator::BlankLine => "blank_line",
+                        ExportSeparator::Custom => "custom",
+                    }
+                }
+            >
+                <option value="newline">"Newline"</option>
+                <option value="blank_line">"Blank line"</option>
+                <option value="custom">"Custom"</option>
+            </select>
+        </div>
+        <Show when=move || separator.get() == ExportSeparator::Custom>
+            <TextControl label="Custom separator" key="export-separator-custom"/>
+        </Show>
+        <ToggleControl label="CRLF line endings" key="export-crlf"/>
+        <TextControl
+            label="Mining tag"
+            key="anki-export-tag"
+            placeholder=ANKI_EXPORT_DEFAULT_TAG
+        />
+        <div class="export_row">
+            <button class="line_button" on:click=copy_joined>
+                "Copy joined"
+            </button>
+            <button
+                class="line_button"
+                on:click=move |_| {
+                    download_text(
+                        "texthooker.md",
+                        &export_markdown(
+                            &lines.get_untracked(),
+                            timestamps.get_untracked(),
+                            &session_title.get_untracked(),
+                            &session_notes.get_untracked(),
+                        ),
+                    );
+                }
+            >
+                "Markdown"
+            </button>
+            <button
+                class="line_button"
+                on:click=move |_| {
+                    download_text(
+                        "texthooker.html",
+                        &export_html(
+                            &lines.get_untracked(),
+                            font_size.get_untracked().0,
+                            text_align.get_untracked().as_css(),
+                            timestamps.get_untracked(),
+                            &session_title.get_untracked(),
+                            &session_notes.get_untracked(),
+                        ),
+                    );
+                }
+            >
+                "HTML"
+            </button>
+            <button
+                class="line_button"
+                on:click=move |_| {
+                    download_text("texthooker.csv", &export_delimited(&lines.get_untracked(), ','));
+                }
+            >
+                "CSV"
+            </button>
+            <button
+                class="line_button"
+                on:click=move |_| {
+                    download_text("texthooker.tsv", &export_delimited(&lines.get_untracked(), '\t'));
+                }
+            >
+                "TSV"
+            </button>
+            <button
+                class="line_button"
+                on:click=move |_| {
+                    download_text(
+                        "texthooker.txt",
+                        &export_plain(
+                            &lines.get_untracked(),
+                            &separator_string(),
+                            timestamps.get_untracked(),
+                            crlf.get_untracked(),
+                        ),
+                    );
+                }
+            >
+                "Text"
+            </button>
+            <button
+                class="line_button"
+                on:click=move |_| {
+                    let tag =
+                        or_default(anki_export_tag.get_untracked(), ANKI_EXPORT_DEFAULT_TAG);
+                    download_text("anki.tsv", &export_anki_tsv(&lines.get_untracked(), &tag));
+                }
+            >
+                "Anki TSV"
+            </button>
+        </div>
+    }
+}
+
+/// Imports a known-words or frequency list (one word per line; only the
+/// first tab-separated column is read) used for unknown-word highlighting.
+#[component]
+fn KnownWordsControl() -> impl IntoView {
+    let (known_words, set_known_words, _) =
+        use_local_storage::<Vec<String>, JsonCodec>("known-words");
+
+    let on_change = move |ev: web_sys::Event| {
+        let input = event_target::<web_sys::HtmlInputElement>(&ev);
+        let Some(file) = input.files().and_then(|files| files.get(0)) else {
+            return;
+        };
+        spawn_local(async move {
+            let Ok(text) = JsFuture::from(file.text()).await else {
+                return;
+            };
+            let Some(text) = text.as_string() else {
+                return;
+            };
+            let words: Vec<String> = text
+                .lines()
+                .filter_map(|line| {
+                    let word = line.split('\t').next()?.trim();
+                    (!word.is_empty()).then(|| word.to_string())
+                })
+                .collect();
+            set_known_words.set(words);
+        });
+    };
+
+    view! {
+        <div class="import_control">
+            <label for="known-words-input">"Import known words"</label>
+            <input id="known-words-input" type="file" accept=".txt,.tsv,.csv" on:change=on_change/>
+            <Show when={move || known_words.with(|words| !words.is_empty())}>
+                <div class="import_status">
+                    {move || format!("{} known words", known_words.with(Vec::len))}
+                </div>
+            </Show>
+        </div>
+    }
+}
+
+/// Imports the bookmark/progress JSON exported by ttsu-style readers,
+/// folding the explored character counts into the session stats.
+#[component]
+fn TtsuImportControl() -> impl IntoView {
+    let (imported_chars, set_imported_chars, _) =
+        use_local_storage::<u64, JsonCodec>("imported-chars");
+
+    let on_change = move |ev: web_sys::Event| {
+        let input = event_target::<web_sys::HtmlInputElement>(&ev);
+        let Some(file) = input.files().and_then(|files| files.get(0)) else {
+            return;
+        };
+        spawn_local(async move {
+            let Ok(text) = JsFuture::from(file.text()).await else {
+                return;
+            };
+            let Some(chars) = text
+                .as_string()
+                .and_then(|text| serde_json::from_str(&text).ok())
+                .as_ref()
+                .and_then(ttsu_explored_chars)
+            else {
+                logging::warn!("unrecognized reader export");
+                return;
+            };
+            set_imported_chars.set(chars);
+        });
+    };
+
+    view! {
+        <div class="import_control">
+            <label for="ttsu-import-input">"Import reader export"</label>
+            <input id="ttsu-import-input" type="file" accept=".json" on:change=on_change/>
+            <Show when={move || imported_chars.get() > 0}>
+                <div class="import_status">
+                    {move || format!("{} imported chars", imported_chars.get())}
+                </div>
+            </Show>
+        </div>
+    }
+}
+
+/// Narrows the view to one speaker's lines — reviewing a single
+/// character's dialogue, or skipping narration by picking any speaker at
+/// all. The empty first option restores the full log.
+#[component]
+fn SpeakerFilterControl() -> impl IntoView {
+    let LiveLines(lines) = expect_context();
+    let (filter, set_filter, _) = use_local_storage::<String, JsonCodec>("speaker-filter");
+    let speakers = create_memo(move |_| {
+        lines.with(|lines| {
+            let mut speakers: Vec<String> =
+                lines.values().filter_map(|line| line.speaker.clone()).collect();
+            speakers.sort();
+            speakers.dedup();
+            speakers
+        })
+    });
+
+    view! {
+        <div class="select_control">
+            <label for="speaker-filter-input">"Show only speaker"</label>
+            <select
+                id="speaker-filter-input"
+                on:change=move |ev| set_filter.set(event_target_value(&ev))
+                prop:value=filter
+            >
+                <option value="">"All speakers"</option>
+                <For
+                    each=move || speakers.get()
+                    key=|name| name.clone()
+                    children=move |name| {
+                        view! { <option value=name.clone()>{name}</option> }
+                    }
+                />
+            </select>
+        </div>
+    }
+}
+
+/// Dropdowns for the language filter: what to do with lines containing no
+/// target-script characters, and which script counts as the target.
+#[component]
+fn LanguageFilterControl() -> impl IntoView {
+    let (filter, set_filter, _) = use_local_storage::<LanguageFilter, JsonCodec>("language-filter");
+    let (script, set_script, _) = use_local_storage::<TargetScript, JsonCodec>("target-script");
+
+    view! {
+        <div class="select_control">
+            <label for="language-filter-input">"Non-target lines"</label>
+            <select
+                id="language-filter-input"
+                on:change=move |ev| {
+                    set_filter.set(match event_target_value(&ev).as_str() {
+                        "drop" => LanguageFilter::Drop,
+                        "flag" => LanguageFilter::Flag,
+                        _ => LanguageFilter::Off,
+                    });
+                }
+                prop:value=move || match filter.get() {
+                    LanguageFilter::Off => "off",
+                    LanguageFilter::Drop => "drop",
+                    LanguageFilter::Flag => "flag",
+                }
+            >
+                <option value="off">"Keep"</option>
+                <option value="drop">"Drop"</option>
+                <option value="flag">"Flag"</option>
+            </select>
+        </div>
+        <div class="select_control">
+            <label for="target-script-input">"Target script"</label>
+            <select
+                id="target-script-input"
+                on:change=move |ev| {
+                    set_script.set(match event_target_value(&ev).as_str() {
+                        "chinese" => TargetScript::Chinese,
+                        "korean" => TargetScript::Korean,
+                        _ => TargetScript::Japanese,
+                    });
+                }
+                prop:value=move || match script.get() {
+                    TargetScript::Japanese => "japanese",
+                    TargetScript::Chinese => "chinese",
+                    TargetScript::Korean => "korean",
+                }
+            >
+                <option value="japanese">"Japanese"</option>
+                <option value="chinese">"Chinese"</option>
+                <option value="korean">"Korean"</option>
+            </select>
+        </div>
+    }
+}
+
+/// The speakers seen so far and the color each one hashes to, shown under
+/// the tinting toggle so the tints are decodable.
+#[component]
+fn SpeakerLegendControl() -> impl IntoView {
+    let LiveLines(lines) = expect_context();
+    let speakers = create_memo(move |_| {
+        lines.with(|lines| {
+            let mut speakers: Vec<String> =
+                lines.values().filter_map(|line| line.speaker.clone()).collect();
+            speakers.sort();
+            speakers.dedup();
+            speakers
+        })
+    });
+
+    view! {
+        <For
+            each=move || speakers.get()
+            key=|name| name.clone()
+            children=move |name| {
+                let color = speaker_color(&name);
+                view! {
+                    <div class="legend_row">
+                        <span
+                            class="legend_swatch"
+                            style=format!("background-color: {color}")
+                        ></span>
+                        {name}
+                    </div>
+                }
+            }
+        />
+    }
+}
+
+/// Upload for a custom notification clip, stored inline as a data URL so
+/// it survives offline and needs no extra hosting; clearing it falls back
+/// to the built-in beep.
+#[component]
+fn NotificationSoundControl() -> impl IntoView {
+    let (sound, set_sound, _) = use_local_storage::<String, JsonCodec>("notify-sound-data");
+
+    let on_change = move |ev: web_sys::Event| {
+        let input = event_target::<web_sys::HtmlInputElement>(&ev);
+        let Some(file) = input.files().and_then(|files| files.get(0)) else {
+            return;
+        };
+        spawn_local(async move {
+            let Ok(buffer) = JsFuture::from(file.array_buffer()).await else {
+                logging::warn!("could not read sound file");
+                return;
+            };
+            let bytes = js_sys::Uint8Array::new(&buffer).to_vec();
+            let encoded = base64::engine::general_purpose::STANDARD.encode(bytes);
+            set_sound.set(format!("data:{};base64,{encoded}", file.type_()));
+        });
+    };
+
+    view! {
+        <div class="import_control">
+            <label for="notify-sound-input">"Notification sound"</label>
+            <input id="notify-sound-input" type="file" accept="audio/*" on:change=on_change/>
+            <Show when={move || !sound.get().is_empty()}>
+                <div class="import_status">
+                    "custom sound set "
+                    <span
+                        class="toast_undo"
+                        on:click=move |_| set_sound.set(String::new())
+                    >
+                        "reset"
+                    </span>
+                </div>
+            </Show>
+        </div>
+    }
+}
+
+/// The Web Speech playback rate; zero (the unset default) means normal
+/// speed.
+#[component]
+fn TtsRateControl() -> impl IntoView {
+    let (rate, set_rate, _) = use_local_storage::<f32, JsonCodec>("tts-rate");
+
+    view! {
+        <NumberControl
+            label="TTS rate"
+            id="tts-rate-input"
+            value=Signal::derive(move || f64::from(rate.get()))
+            set_value=move |value: f64| set_rate.set(value as f32)
+            min=0.0
+            max=10.0
+            step=0.1
+        />
+    }
+}
+
+/// Dropdown for the line-text wrapping mode.
+#[component]
+fn WrapControl() -> impl IntoView {
+    let (wrap_mode, set_wrap_mode, _) = use_local_storage::<WrapMode, JsonCodec>("wrap-mode");
+
+    view! {
+        <div class="select_control">
+            <label for="wrap-mode-input">"Word wrap"</label>
+            <select
+                id="wrap-mode-input"
+                on:change=move |ev| {
+                    set_wrap_mode.set(match event_target_value(&ev).as_str() {
+                        "break-all" => WrapMode::BreakAll,
+                        "keep-all" => WrapMode::KeepAll,
+                        "scroll" => WrapMode::Scroll,
+                        _ => WrapMode::Normal,
+                    });
+                }
+                prop:value=move || match wrap_mode.get() {
+                    WrapMode::Normal => "normal",
+                    WrapMode::BreakAll => "break-all",
+                    WrapMode::KeepAll => "keep-all",
+                    WrapMode::Scroll => "scroll",
+                }
+            >
+                <option value="normal">"Normal"</option>
+                <option value="break-all">"Break anywhere"</option>
+                <option value="keep-all">"Keep words whole"</option>
+                <option value="scroll">"Scroll sideways"</option>
+            </select>
+        </div>
+    }
+}
+
+/// How often the stats push fires, in seconds; zero falls back to the
+/// default interval.
+#[component]
+fn StatsIntervalControl() -> impl IntoView {
+    let (interval, set_interval, _) = use_local_storage::<u32, JsonCodec>("stats-push-interval");
+
+    view! {
+        <NumberControl
+            label="Push interval (s)"
+            id="stats-interval-input"
+            value=Signal::derive(move || f64::from(interval.get()))
+            set_value=move |value: f64| set_interval.set(value as u32)
+            min=0.0
+            max=86400.0
+            step=1.0
+        />
+    }
+}
+
+/// The note-field mapping editor: one row per mapped field, each picking
+/// what data it receives and whether sends overwrite or append.
+#[cfg(feature = "anki")]
+#[component]
+fn AnkiFieldsControl() -> impl IntoView {
+    let (mappings, set_mappings, _) =
+        use_local_storage::<Vec<FieldMapping>, JsonCodec>("anki-field-mappings");
+
+    view! {
+        <For
+            each=move || 0..mappings.with(Vec::len)
+            key=|index| *index
+            children=move |index| {
+                view! {
+                    <div class="mapping_row">
+                        <input
+                            type="text"
+                            placeholder="Field name"
+                            prop:value=move || {
+                                mappings
+                                    .with(|mappings| {
+                                        mappings.get(index).map(|m| m.field.clone())
+                                    })
+                                    .unwrap_or_default()
+                            }
+                            on:change=move |ev| {
+                                set_mappings.update(|mappings| {
+                                    if let Some(mapping) = mappings.get_mut(index) {
+                                        mapping.field = event_target_value(&ev);
+                                    }
+                                });
+                            }
+                        />
+                        <select
+                            on:change=move |ev| {
+                                set_mappings.update(|mappings| {
+                                    if let Some(mapping) = mappings.get_mut(index) {
+                                        mapping.source = match event_target_value(&ev).as_str() {
+                                            "context" => FieldSource::Context,
+                                            "session" => FieldSource::Session,
+                                            "timestamp" => FieldSource::Timestamp,
+                                            _ => FieldSource::Sentence,
+                                        };
+                                    }
+                                });
+                            }
+                            prop:value=move || {
+                                match mappings
+                                    .with(|mappings| {
+                                        mappings.get(index).map(|m| m.source)
+                                    })
+                                    .unwrap_or_default()
+                                {
+                                    FieldSource::Sentence => "sentence",
+                                    FieldSource::Context => "context",
+                                    FieldSource::Session => "session",
+                                    FieldSource::Timestamp => "timestamp",
+                                }
+                            }
+                        >
+                            <option value="sentence">"Sentence"</option>
+                            <option value="context">"Context"</option>
+                            <option value="session">"Session"</option>
+                            <option value="timestamp">"Timestamp"</option>
+                        </select>
+                        <select
+                            on:change=move |ev| {
+                                set_mappings.update(|mappings| {
+                                    if let Some(mapping) = mappings.get_mut(index) {
+                                        mapping.mode =
+                                            match event_target_value(&ev).as_str() {
+                                                "append" => FieldMode::Append,
+                                                _ => FieldMode::Overwrite,
+                                            };
+                                    }
+                                });
+                            }
+                            prop:value=move || {
+                                match mappings
+                                    .with(|mappings| mappings.get(index).map(|m| m.mode))
+                                    .unwrap_or_default()
+                                {
+                                    FieldMode::Overwrite => "overwrite",
+                                    FieldMode::Append => "append",
+                                }
+                            }
+                        >
+                            <option value="overwrite">"Overwrite"</option>
+                            <option value="append">"Append"</option>
+                        </select>
+                        <button
+                            class="line_button"
+                            title="Remove mapping"
+                            aria-label="Remove mapping"
+                            on:click=move |_| {
+                                set_mappings.update(|mappings| {
+                                    if index < mappings.len() {
+                                        mappings.remove(index);
+                                    }
+                                });
+                            }
+                        >
+                            <IconView icon=Icon::Close/>
+                        </button>
+                    </div>
+                }
+            }
+        />
+        <button
+            class="line_button"
+            on:click=move |_| {
+                set_mappings.update(|mappings| mappings.push(FieldMapping::default()));
+            }
+        >
+            "Add field"
+        </button>
+    }
+}
+
+/// How many of the newest lines the overlay keeps on screen when `?overlay`
+/// is given without a count.
+const OVERLAY_DEFAULT_LINES: usize = 3;
+
+/// Returns the line count for OBS overlay mode if the page was opened with
+/// the `?overlay[=N]` query parameter.
+fn overlay_mode() -> Option<usize> {
+    let search = window().location().search().expect("valid call");
+    let params = web_sys::UrlSearchParams::new_with_str(&search).expect("valid call");
+    params
+        .get("overlay")
+        .map(|count| count.parse().unwrap_or(OVERLAY_DEFAULT_LINES))
+}
+
+/// The record-a-key shortcut editor: click a binding, press the new key.
+/// Backspace unbinds, Escape cancels.
+#[component]
+fn ShortcutsControl() -> impl IntoView {
+    let (bindings, set_bindings, _) = use_local_storage::<KeyBindings, JsonCodec>("key-bindings");
+    let RecordingAction(recording) = expect_context();
+
+    let _ = use_event_listener(window(), ev::keydown, move |ev| {
+        if ev.is_composing() {
+            return;
+        }
+        let Some(action) = recording.get_untracked() else {
+            return;
+        };
+        ev.prevent_default();
+        match ev.key().as_str() {
+            // Wait for a complete chord, not a bare modifier.
+            "Control" | "Alt" | "Shift" | "Meta" => return,
+            "Escape" => {}
+            "Backspace" | "Delete" => {
+                set_bindings.update(|bindings| {
+                    bindings.0.shift_remove(&action);
+                });
+            }
+            _ => {
+                set_bindings.update(|bindings| {
+                    bindings.0.insert(action, KeyBinding::from_event(&ev));
+                });
+            }
+        }
+        recording.set(None);
+    });
+
+    view! {
+        {Action::ALL
+            .iter()
+            .map(|&action| {
+                view! {
+                    <div class="shortcut_row">
+                        <span class="shortcut_label">{action.label()}</span>
+                        <button
+                            class="shortcut_key"
+                            class:recording=move || recording.get() == Some(action)
+                            title="Click, then press a key. Backspace unbinds, Escape cancels."
+                            on:click=move |_| recording.set(Some(action))
+                        >
+                            {move || {
+                                if recording.get() == Some(action) {
+                                    "press a key".to_string()
+                                } else {
+                                    bindings
+                                        .with(|bindings| {
+                                            bindings
+                                                .0
+                                                .get(&action)
+                                                .map_or("unbound".to_string(), ToString::to_string)
+                                        })
+                                }
+                            }}
+                        </button>
+                    </div>
+                }
+            })
+            .collect_view()}
+    }
+}
+
+/// Dropdown for how the newest line is highlighted.
+#[component]
+fn HighlightControl() -> impl IntoView {
+    let (highlight, set_highlight, _) =
+        use_local_storage::<HighlightStyle, JsonCodec>("highlight-newest");
+
+    view! {
+        <div id="highlight-container">
+            <label for="highlight-input">"Highlight new"</label>
+            <select
+                id="highlight-input"
+                on:change=move |ev| {
+                    set_highlight.set(match event_target_value(&ev).as_str() {
+                        "flash" => HighlightStyle::Flash,
+                        "tint" => HighlightStyle::Tint,
+                        _ => HighlightStyle::Off,
+                    });
+                }
+                prop:value=move || {
+                    match highlight.get() {
+                        HighlightStyle::Off => "off",
+                        HighlightStyle::Flash => "flash",
+                        HighlightStyle::Tint => "tint",
+                    }
+                }
+            >
+                <option value="off">"Off"</option>
+                <option value="flash">"Flash"</option>
+                <option value="tint">"Tint"</option>
+            </select>
+        </div>
+    }
+}
+
+/// Dropdowns for the text alignment and base direction of the line text.
+#[component]
+fn AlignmentControl() -> impl IntoView {
+    let (text_align, set_text_align, _) = use_local_storage::<TextAlign, JsonCodec>("text-align");
+    let (direction, set_direction, _) =
+        use_local_storage::<BaseDirection, JsonCodec>("direction");
+
+    view! {
+        <div id="text-align-container">
+            <label for="text-align-input">"Align"</label>
+            <select
+                id="text-align-input"
+                on:change=move |ev| {
+                    set_text_align.set(match event_target_value(&ev).as_str() {
+                        "center" => TextAlign::Center,
+                        "right" => TextAlign::Right,
+                        "justify" => TextAlign::Justify,
+                        _ => TextAlign::Left,
+                    });
+                }
+                prop:value=move || text_align.get().as_css()
+            >
+                <option value="left">"Left"</option>
+                <option value="center">"Center"</option>
+                <option value="right">"Right"</option>
+                <option value="justify">"Justify"</option>
+            </select>
+        </div>
+        <div id="direction-container">
+            <label for="direction-input">"Direction"</label>
+            <select
+                id="direction-input"
+                on:change=move |ev| {
+                    set_direction.set(match event_target_value(&ev).as_str() {
+                        "rtl" => BaseDirection::Rtl,
+                        _ => BaseDirection::Ltr,
+                    });
+                }
+                prop:value=move || direction.get().as_css()
+            >
+                <option value="ltr">"LTR"</option>
+                <option value="rtl">"RTL"</option>
+            </select>
+        </div>
+    }
+}
+
+/// Dropdown for the control density: how large the toolbar and per-line
+/// buttons render.
+#[component]
+fn DensityControl() -> impl IntoView {
+    let (density, set_density, _) = use_local_storage::<Density, JsonCodec>("density");
+
+    view! {
+        <div id="density-container">
+            <label for="density-input">"Controls"</label>
+            <select
+                id="density-input"
+                on:change=move |ev| {
+                    set_density.set(match event_target_value(&ev).as_str() {
+                        "compact" => Density::Compact,
+                        "large" => Density::Large,
+                        _ => Density::Normal,
+                    });
+                }
+                prop:value=move || match density.get() {
+                    Density::Compact => "compact",
+                    Density::Normal => "normal",
+                    Density::Large => "large",
+                }
+            >
+                <option value="compact">"Compact"</option>
+                <option value="normal">"Normal"</option>
+                <option value="large">"Large"</option>
+            </select>
+        </div>
+    }
+}
+
+/// How often session stats are pushed unless the user configures otherwise.
+const STATS_PUSH_DEFAULT_INTERVAL_SECS: u32 = 30;
+
+/// The JSON shape pushed to external trackers unless the user configures
+/// otherwise; `{chars}`, `{seconds}`, and `{session}` are substituted
+/// before sending.
+const STATS_PUSH_DEFAULT_TEMPLATE: &str = r#"{"characters":{chars},"seconds":{seconds}}"#;
+
+/// Fills a stats template with the current session numbers. The session
+/// title is escaped so it can sit inside a JSON string in the template.
+fn stats_payload(template: &str, chars: usize, seconds: u64, session: &str) -> String {
+    template
+        .replace("{chars}", &chars.to_string())
+        .replace("{seconds}", &seconds.to_string())
+        .replace("{session}", &session.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// POSTs a raw body, ignoring the response; stats pushes are fire-and-forget.
+async fn http_post(url: &str, body: &str) {
+    let mut init = web_sys::RequestInit::new();
+    init.method("POST");
+    init.body(Some(&JsValue::from_str(body)));
+    let Ok(request) = web_sys::Request::new_with_str_and_init(url, &init) else {
+        return;
+    };
+    let _ = JsFuture::from(window().fetch_with_request(&request)).await;
+}
+
+/// Saves a string client-side through a temporary object URL on an invisible
+/// anchor.
+fn download_text(name: &str, text: &str) {
+    let blob =
+        Blob::new_with_str_sequence(&js_sys::Array::of1(&text.into())).expect("valid blob");
+    let url = Url::create_object_url_with_blob(&blob).expect("valid url");
+    let anchor = document()
+        .create_element("a")
+        .expect("valid element type")
+        .dyn_into::<web_sys::HtmlAnchorElement>()
+        .expect("valid element type");
+    anchor.set_href(&url);
+    anchor.set_download(name);
+    anchor.click();
+    Url::revoke_object_url(&url).expect("valid call");
+}
+
+/// Formats an epoch-milliseconds arrival time as a local clock time.
+fn format_timestamp(ms: f64) -> String {
+    js_sys::Date::new(&JsValue::from_f64(ms))
+        .to_locale_time_string("default")
+        .into()
+}
+
+/// Renders the log as a Markdown document: the session title and notes as a
+/// header when set, then one paragraph per line, with the speaker and the
+/// arrival time (when timestamps are included) as a blockquote above it.
+fn export_markdown(lines: &LineMap, timestamps: bool, title: &str, notes: &str) -> String {
+    let mut out = String::new();
+    if !title.is_empty() {
+        out.push_str(&format!("# {title}\n\n"));
+    }
+    if !notes.is_empty() {
+        out.push_str(&format!("{notes}\n\n"));
+    }
+    for line in lines.values() {
+        let mut quote = String::new();
+        if let Some(speaker) = &line.speaker {
+            quote.push_str(&format!("> {speaker}\n"));
+        }
+        if timestamps {
+            if let Some(added_at) = line.added_at {
+                quote.push_str(&format!("> {}\n", format_timestamp(added_at)));
+            }
+        }
+        if !quote.is_empty() {
+            out.push_str(&quote);
+            out.push_str(">\n");
+        }
+        out.push_str(&line.text);
+        out.push_str("\n\n");
+    }
+    out
+}
+
+/// Fetches and parses a JSON document.
+async fn http_get_json(url: &str) -> Option<serde_json::Value> {
+    let response = JsFuture::from(window().fetch_with_str(url)).await.ok()?;
+    let response: web_sys::Response = response.unchecked_into();
+    let text = JsFuture::from(response.text().ok()?).await.ok()?;
+    serde_json::from_str(&text.as_string()?).ok()
+}
+
+/// Fetches `url` as text, bypassing both the HTTP cache and the service
+/// worker cache.
+async fn http_get_text_nocache(url: &str) -> Option<String> {
+    let mut init = web_sys::RequestInit::new();
+    init.method("GET");
+    init.cache(web_sys::RequestCache::NoStore);
+    let request = web_sys::Request::new_with_str_and_init(url, &init).ok()?;
+    let response = JsFuture::from(window().fetch_with_request(&request)).await.ok()?;
+    let response: web_sys::Response = response.unchecked_into();
+    let text = JsFuture::from(response.text().ok()?).await.ok()?;
+    text.as_string()
+}
+
+/// Pulls the fingerprinted `.wasm` asset path out of an index.html, the
+/// simplest stable identity for a deployed build.
+fn wasm_asset(html: &str) -> Option<String> {
+    let end = html.find("_bg.wasm")? + "_bg.wasm".len();
+    let start = html[..end].rfind('"')? + 1;
+    Some(html[start..end].to_string())
+}
+
+/// How often to look for a newly deployed build, in seconds.
+const UPDATE_CHECK_INTERVAL_SECS: u64 = 3600;
+
+/// Shows a "refresh to update" banner once the served index.html references
+/// a different wasm build than the one this page booted from. Without the
+/// check, the service worker would keep serving the cached build with
+/// nothing ever suggesting a newer one exists.
+#[component]
+fn UpdateBanner() -> impl IntoView {
+    let update_available = create_rw_signal(false);
+    let booted = store_value(
+        document()
+            .document_element()
+            .map(|element| element.outer_html())
+            .and_then(|html| wasm_asset(&html)),
+    );
+    let check = move || {
+        spawn_local(async move {
+            let Some(booted) = booted.get_value() else {
+                return;
+            };
+            let Some(html) = http_get_text_nocache("index.html").await else {
+                return;
+            };
+            if wasm_asset(&html).is_some_and(|asset| asset != booted) {
+                update_available.set(true);
+            }
+        });
+    };
+    check();
+    set_interval(check, Duration::from_secs(UPDATE_CHECK_INTERVAL_SECS));
+
+    view! {
+        <Show when=move || update_available.get()>
+            <div
+                class="update_banner"
+                on:click=|_| {
+                    let _ = window().location().reload();
+                }
+            >
+                "A new version is available — refresh to update"
+            </div>
+        </Show>
+    }
+}
+
+/// How often the WebDAV backup is pushed, in seconds.
+const WEBDAV_BACKUP_INTERVAL_SECS: u64 = 300;
+
+/// PUTs the backup JSON to a WebDAV URL with basic auth, fire-and-forget.
+async fn webdav_put(url: &str, username: &str, password: &str, body: &str) {
+    let credentials =
+        base64::engine::general_purpose::STANDARD.encode(format!("{username}:{password}"));
+    let headers = web_sys::Headers::new().expect("valid call");
+    headers
+        .set("Authorization", &format!("Basic {credentials}"))
+        .expect("valid call");
+    let mut init = web_sys::RequestInit::new();
+    init.method("PUT");
+    init.headers(&headers);
+    init.body(Some(&JsValue::from_str(body)));
+    let Ok(request) = web_sys::Request::new_with_str_and_init(url, &init) else {
+        return;
+    };
+    let _ = JsFuture::from(window().fetch_with_request(&request)).await;
+}
+
+/// Escapes text for embedding in the HTML export.
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Renders the log as a self-contained HTML page styled like the app, for
+/// archiving a finished script in readable form.
+fn export_html(
+    lines: &LineMap,
+    font_size: u32,
+    align: &str,
+    timestamps: bool,
+    title: &str,
+    notes: &str,
+) -> String {
+    let page_title = if title.is_empty() { "Texthooker log" } else { title };
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\"/>\n\
+         <title>{}</title>\n<style>\n\
+         body {{ background-color: #202020; color: #bdbdbd; line-height: 150%; \
+         margin: 1% 10% 5% 1.5%; font-family: \"Noto Sans JP\", sans-serif; \
+         font-size: {font_size}px; text-align: {align}; }}\n\
+         p {{ margin-top: 24px; white-space: pre-wrap; }}\n\
+         .timestamp {{ color: #606060; font-size: 0.6em; display: block; }}\n\
+         .notes {{ color: #9d9d9d; font-size: 0.7em; }}\n\
+         </style>\n</head>\n<body>\n",
+        escape_html(page_title)
+    );
+    if !title.is_empty() {
+        out.push_str(&format!("<h1>{}</h1>\n", escape_html(title)));
+    }
+    if !notes.is_empty() {
+        out.push_str(&format!("<p class=\"notes\">{}</p>\n", escape_html(notes)));
+    }
+    for line in lines.values() {
+        out.push_str("<p>");
+        if timestamps {
+            if let Some(added_at) = line.added_at {
+                out.push_str(&format!(
+                    "<span class=\"timestamp\">{}</span>",
+                    escape_html(&format_timestamp(added_at))
+                ));
+            }
+        }
+        out.push_str(&escape_html(&line.text));
+        out.push_str("</p>\n");
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+/// Escapes one CSV/TSV field, quoting when the delimiter, quotes, or
+/// newlines appear in the value.
+fn escape_delimited(field: &str, sep: char) -> String {
+    if field.contains([sep, '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Renders the log as delimiter-separated rows with a metadata header, for
+/// spreadsheet processing or bulk Anki import. Tags are space-separated;
+/// the notes column is reserved and currently empty.
+fn export_delimited(lines: &LineMap, sep: char) -> String {
+    let mut out = ["id", "timestamp", "speaker", "tags", "notes", "text"]
+        .join(&sep.to_string());
+    out.push('\n');
+    for (id, line) in lines {
+        let timestamp = line.added_at.map(format_timestamp).unwrap_or_default();
+        let row = [
+            id.to_string(),
+            timestamp,
+            line.speaker.clone().unwrap_or_default(),
+            line.tags.join(" "),
+            String::new(),
+            line.text.clone(),
+        ];
+        let row: Vec<_> = row.iter().map(|field| escape_delimited(field, sep)).collect();
+        out.push_str(&row.join(&sep.to_string()));
+        out.push('\n');
+    }
+    out
+}
+
+/// The separator placed between lines in the plain-text export.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+enum ExportSeparator {
+    #[default]
+    Newline,
+    BlankLine,
+    Custom,
+}
+
+/// Renders the log as plain text, one line per entry with an optional
+/// bracketed timestamp prefix, joined by the configured separator.
+fn export_plain(lines: &LineMap, separator: &str, timestamps: bool, crlf: bool) -> String {
+    let text = lines
+        .values()
+        .map(|line| match line.added_at.filter(|_| timestamps) {
+            Some(added_at) => format!("[{}] {}", format_timestamp(added_at), line.text),
+            None => line.text.clone(),
+        })
+        .collect::<Vec<_>>()
+        .join(separator);
+    if crlf {
+        text.replace('\n', "\r\n")
+    } else {
+        text
+    }
+}
+
+/// The tag the Anki TSV export looks for unless the user configures
+/// otherwise.
+const ANKI_EXPORT_DEFAULT_TAG: &str = "mine";
+
+/// Renders the tagged lines as a headerless TSV ready for Anki import:
+/// sentence, previous line, next line, and the tag itself as columns.
+fn export_anki_tsv(lines: &LineMap, tag: &str) -> String {
+    let mut out = String::new();
+    for index in 0..lines.len() {
+        let (_, line) = lines.get_index(index).expect("valid index");
+        if !line.tags.iter().any(|t| t == tag) {
+            continue;
+        }
+        let context = |index: Option<usize>| {
+            index
+                .and_then(|index| lines.get_index(index))
+                .map(|(_, line)| line.text.clone())
+                .unwrap_or_default()
+        };
+        let row = [
+            line.text.clone(),
+            context(index.checked_sub(1)),
+            context(Some(index + 1)),
+            tag.to_string(),
+        ];
+        let row: Vec<_> = row.iter().map(|field| escape_delimited(field, '\t')).collect();
+        out.push_str(&row.join("\t"));
+        out.push('\n');
+    }
+    out
+}
+
+/// Sums the explored character counts from a ttsu-style reader export,
+/// which is either a bare array of book entries or an object wrapping one.
+fn ttsu_explored_chars(json: &serde_json::Value) -> Option<u64> {
+    let entries = json.as_array().or_else(|| {
+        ["bookmark", "bookmarks", "data"]
+            .iter()
+            .find_map(|key| json.get(*key)?.as_array())
+    })?;
+    let chars = entries
+        .iter()
+        .filter_map(|entry| entry.get("exploredCharCount")?.as_u64())
+        .sum();
+    (chars > 0).then_some(chars)
+}
+
+/// Sends an image to the OCR endpoint and returns the recognized text. The
+/// response may be a bare string or a JSON object with a `text` field,
+/// covering owocr/manga-ocr style servers and hosted APIs alike.
+#[cfg(feature = "ocr")]
+async fn ocr_image(url: &str, image: &web_sys::Blob) -> Option<String> {
+    let mut init = web_sys::RequestInit::new();
+    init.method("POST");
+    init.body(Some(image.as_ref()));
+    let request = web_sys::Request::new_with_str_and_init(url, &init).ok()?;
+    let response = JsFuture::from(window().fetch_with_request(&request))
+        .await
+        .ok()?;
+    let response: web_sys::Response = response.unchecked_into();
+    let text = JsFuture::from(response.text().ok()?).await.ok()?.as_string()?;
+    match serde_json::from_str::<serde_json::Value>(&text) {
+        Ok(value) => value
+            .get("text")
+            .and_then(|text| text.as_str())
+            .or(value.as_str())
+            .map(|text| text.trim().to_string()),
+        Err(_) => Some(text.trim().to_string()),
+    }
+}
+
+/// Plays the new-line notification sound: the uploaded clip when one is
+/// stored (as a data URL), otherwise a short synthesized beep so the
+/// feature works without any upload.
+fn play_notification(custom: &str) {
+    if !custom.is_empty() {
+        if let Ok(audio) = web_sys::HtmlAudioElement::new_with_src(custom) {
+            let _ = audio.play();
+        }
+        return;
+    }
+    let Ok(context) = web_sys::AudioContext::new() else {
+        return;
+    };
+    let (Ok(oscillator), Ok(gain)) = (context.create_oscillator(), context.create_gain()) else {
+        return;
+    };
+    oscillator.frequency().set_value(880.0);
+    gain.gain().set_value(0.05);
+    let _ = oscillator.connect_with_audio_node(&gain);
+    let _ = gain.connect_with_audio_node(&context.destination());
+    let _ = oscillator.start();
+    let _ = oscillator.stop_with_when(context.current_time() + 0.15);
+}
+
+/// Reads text aloud through the Web Speech API, matching the configured
+/// voice by name prefix; a zero rate falls back to normal speed.
+fn speak(text: &str, voice: &str, rate: f32) {
+    let Ok(utterance) = web_sys::SpeechSynthesisUtterance::new_with_text(text) else {
+        return;
+    };
+    utterance.set_rate(if rate <= 0.0 { 1.0 } else { rate });
+    let Ok(synthesis) = window().speech_synthesis() else {
+        return;
+    };
+    if !voice.is_empty() {
+        let voices = synthesis.get_voices();
+        for index in 0..voices.length() {
+            let candidate: web_sys::SpeechSynthesisVoice = voices.get(index).unchecked_into();
+            if candidate.name().starts_with(voice) {
+                utterance.set_voice(Some(&candidate));
+                break;
+            }
+        }
+    }
+    synthesis.speak(&utterance);
+}
+
+/// Extracts the line text from a websocket frame, auto-detecting the
+/// protocol. Agent sends JSON objects carrying the sentence alongside
+/// metadata (process name, timestamps), while mpv_websocket and Textractor
+/// forwarders send the bare text; a frame that parses as a JSON object is
+/// treated as the former.
+fn extract_frame_text(frame: &str) -> Option<String> {
+    let Ok(serde_json::Value::Object(map)) = serde_json::from_str(frame) else {
+        return Some(frame.to_string());
+    };
+    ["sentence", "text"]
+        .iter()
+        .find_map(|key| map.get(*key)?.as_str())
+        .map(str::to_string)
+}
+
+/// Connects to an mpv_websocket / mpv-subs-forwarder server and feeds each
+/// received subtitle line into `on_text`. State transitions are reported
+/// through `on_status` for the diagnostics panel. The connection is made
+/// once at startup; reconnecting means reloading the page.
+fn setup_websocket(
+    url: &str,
+    on_text: impl Fn(String) + Copy + 'static,
+    on_status: impl Fn(String) + Copy + 'static,
+) {
+    let Ok(socket) = web_sys::WebSocket::new(url) else {
+        logging::warn!("invalid websocket URL: {url}");
+        on_status(format!("websocket URL invalid: {url}"));
+        return;
+    };
+    on_status(format!("websocket connecting to {url}"));
+    let callback = Closure::<dyn Fn(web_sys::MessageEvent)>::new(move |ev: web_sys::MessageEvent| {
+        if let Some(text) = ev.data().as_string().as_deref().and_then(extract_frame_text) {
+            if !text.is_empty() {
+                on_text(text);
+            }
+        }
+    });
+    socket.set_onmessage(Some(callback.as_ref().unchecked_ref()));
+    // The socket lives for the life of the page.
+    callback.forget();
+    let on_open = Closure::<dyn Fn()>::new(move || on_status("websocket connected".to_string()));
+    socket.set_onopen(Some(on_open.as_ref().unchecked_ref()));
+    on_open.forget();
+    let on_close = Closure::<dyn Fn()>::new(move || on_status("websocket closed".to_string()));
+    socket.set_onclose(Some(on_close.as_ref().unchecked_ref()));
+    on_close.forget();
+    let on_error = Closure::<dyn Fn()>::new(move || on_status("websocket error".to_string()));
+    socket.set_onerror(Some(on_error.as_ref().unchecked_ref()));
+    on_error.forget();
+}
+
+/// Optional built-in popup dictionary (`--features dictionary`): a
+/// JMdict-derived `jmdict.json` file (word to glosses) served next to the
+/// app is fetched on first use and looked up against the current selection,
+/// for users who can't install a browser extension.
+#[cfg(feature = "dictionary")]
+mod dictionary {
+    use std::collections::HashMap;
+    use std::rc::Rc;
+
+    use leptos::*;
+    use leptos_use::storage::use_local_storage;
+    use leptos_use::utils::JsonCodec;
+
+    type Glosses = HashMap<String, Vec<String>>;
+
+    /// Finds the longest prefix of the selection that has an entry, so
+    /// selecting a little past a word still hits it.
+    fn lookup(dictionary: &Glosses, selection: &str) -> Option<(String, Vec<String>)> {
+        let chars: Vec<char> = selection.chars().collect();
+        (1..=chars.len()).rev().find_map(|len| {
+            let candidate: String = chars[..len].iter().collect();
+            let glosses = dictionary.get(&candidate)?.clone();
+            Some((candidate, glosses))
+        })
+    }
+
+    /// Shows glosses for the selected word while the toggle is on. The
+    /// dictionary file is only fetched on the first lookup.
+    #[component]
+    pub fn DictionaryPopup(
+        #[prop(into)] selected_text: Signal<Option<String>>,
+    ) -> impl IntoView {
+        let (enabled, _, _) = use_local_storage::<bool, JsonCodec>("popup-dictionary");
+        let dictionary = store_value(None::<Rc<Glosses>>);
+        let result = create_rw_signal(None::<(String, Vec<String>)>);
+
+        create_effect(move |_| {
+            let Some(selection) = selected_text.get().filter(|text| !text.is_empty()) else {
+                result.set(None);
+                return;
+            };
+            if !enabled.get_untracked() {
+                return;
+            }
+            match dictionary.get_value() {
+                Some(dictionary) => result.set(lookup(&dictionary, &selection)),
+                None => spawn_local(async move {
+                    let Some(value) = crate::http_get_json("jmdict.json").await else {
+                        return;
+                    };
+                    let Ok(entries) = serde_json::from_value::<Glosses>(value) else {
+                        return;
+                    };
+                    let entries = Rc::new(entries);
+                    result.set(lookup(&entries, &selection));
+                    dictionary.set_value(Some(entries));
+                }),
+            }
+        });
+
+        view! {
+            <Show when=move || result.with(Option::is_some)>
+                <div class="dictionary_popup">
+                    <span class="dictionary_word">
+                        {move || result.get().map(|(word, _)| word)}
+                    </span>
+                    {move || result.get().map(|(_, glosses)| glosses.join("; "))}
+                </div>
+            </Show>
+        }
+    }
+}
+
+/// Splits text into known and unknown runs by greedy longest-match against
+/// the imported word list. A real tokenizer would segment better, but this
+/// gives useful difficulty feedback without one; only CJK ideographs are
+/// ever flagged unknown, so kana and punctuation stay unmarked.
+fn split_known(text: &str, known: &HashSet<String>, max_len: usize) -> Vec<(String, bool)> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut runs: Vec<(String, bool)> = Vec::new();
+    let push = |piece: String, flag: bool, runs: &mut Vec<(String, bool)>| {
+        match runs.last_mut() {
+            Some((run, existing)) if *existing == flag => run.push_str(&piece),
+            _ => runs.push((piece, flag)),
+        }
+    };
+    let mut index = 0;
+    while index < chars.len() {
+        let matched = (1..=max_len.min(chars.len() - index))
+            .rev()
+            .find(|len| known.contains(&chars[index..index + len].iter().collect::<String>()));
+        match matched {
+            Some(len) => {
+                push(chars[index..index + len].iter().collect(), true, &mut runs);
+                index += len;
+            }
+            None => {
+                let c = chars[index];
+                let ideograph = ('\u{4e00}'..='\u{9fff}').contains(&c);
+                push(c.to_string(), !ideograph, &mut runs);
+                index += 1;
+            }
+        }
+    }
+    runs
+}
+
+/// How long the selection must hold still before it is materialized, in
+/// milliseconds.
+const SELECTION_DEBOUNCE_MS: u64 = 200;
+
+/// Tracks the current document selection as a string, for the dictionary
+/// popup and the recent-lookup history. `selectionchange` fires continuously
+/// while dragging across a large log, so building the string is debounced
+/// until the selection settles; anything needing the instantaneous value
+/// reads [`current_selection`] directly instead.
+fn use_selected_text() -> Signal<Option<String>> {
+    let (text, set_text) = create_signal(None);
+    let pending = store_value(None::<leptos_dom::helpers::TimeoutHandle>);
+    let _ = use_event_listener(document(), ev::selectionchange, move |_| {
+        if let Some(handle) = pending.try_update_value(Option::take).flatten() {
+            handle.clear();
+        }
+        let handle = set_timeout_with_handle(
+            move || set_text.set(current_selection()),
+            Duration::from_millis(SELECTION_DEBOUNCE_MS),
+        )
+        .expect("timeout should be set");
+        pending.set_value(Some(handle));
+    });
+    text.into()
+}
+
+/// Materializes the document selection as a string on demand.
+fn current_selection() -> Option<String> {
+    let selection = window().get_selection().expect("valid call")?;
+    Some(selection.to_string().as_string().expect("a string"))
+}
+
+/// Extracts the text of a hooked node. `<ruby>` annotations (emitted by some
+/// hooks and e-book pipelines) are kept in a normalized
+/// `<ruby>base<rt>reading</rt></ruby>` form that [`LineView`] renders back
+/// into real ruby; everything else is flattened to plain text.
+fn extract_text(node: &web_sys::Node) -> String {
+    let has_ruby = node
+        .dyn_ref::<web_sys::Element>()
+        .is_some_and(|element| {
+            element
+                .query_selector("ruby")
+                .expect("valid selector")
+                .is_some()
+        });
+    if !has_ruby {
+        return node.text_content().expect("has text content");
+    }
+    let mut out = String::new();
+    let children = node.child_nodes();
+    for i in 0..children.length() {
+        let child = children.get(i).expect("in bounds");
+        if child.node_name() != "RUBY" {
+            out.push_str(&extract_text(&child));
+            continue;
+        }
+        let mut base = String::new();
+        let mut reading = String::new();
+        let parts = child.child_nodes();
+        for j in 0..parts.length() {
+            let part = parts.get(j).expect("in bounds");
+            let text = part.text_content().unwrap_or_default();
+            match part.node_name().as_str() {
+                "RT" => reading.push_str(&text),
+                "RP" => {}
+                _ => base.push_str(&text),
+            }
+        }
+        out.push_str(&format!("<ruby>{base}<rt>{reading}</rt></ruby>"));
+    }
+    out
+}
+
+/// Owns the capture [`MutationObserver`] and its callback, so capture can be
+/// disconnected, reconnected, and retargeted at runtime instead of leaking a
+/// permanently attached observer.
+///
+/// The observer watches its target for `<p>` nodes appended by a clipboard
+/// inserter extension, strips them back out of the DOM, and hands their text
+/// to the callback. All nodes from one batch of mutation records are
+/// delivered in a single call, so backlog dumps cost one update rather than
+/// one per line.
+struct ObserverHandle {
+    observer: MutationObserver,
+    // Keeps the JS callback alive for as long as the observer is.
+    _callback: Closure<dyn Fn(Vec<MutationRecord>)>,
+    connected: bool,
+}
+
+impl ObserverHandle {
+    fn new(on_texts: impl Fn(Vec<String>) + 'static) -> Self {
+        let callback =
+            Closure::<dyn Fn(Vec<MutationRecord>)>::new(move |records: Vec<MutationRecord>| {
+                let mut texts = Vec::new();
+                for record in records {
+                    let added = record.added_nodes();
+                    for i in 0..added.length() {
+                        let node = added.get(i).expect("in bounds");
+                        if node.node_name() != "P" {
+                            continue;
+                        }
+                        texts.push(extract_text(&node));
+                        if let Some(parent) = node.parent_node() {
+                            parent.remove_child(&node).expect("valid call");
+                        }
+                    }
+                }
+                if !texts.is_empty() {
+                    on_texts(texts);
+                }
+            });
+        let observer =
+            MutationObserver::new(callback.as_ref().unchecked_ref()).expect("callback is valid");
+        Self {
+            observer,
+            _callback: callback,
+            connected: false,
+        }
+    }
+
+    /// Observes the element matching `selector` (the document body when the
+    /// selector is empty or matches nothing), replacing any previous target.
+    fn observe(&mut self, selector: &str) {
+        self.disconnect();
+        let body = document().body().expect("body exists");
+        let target = match selector {
+            "" => body.into(),
+            selector => document()
+                .query_selector(selector)
+                .ok()
+                .flatten()
+                .map_or_else(|| body.into(), web_sys::Node::from),
+        };
+        let mut options = MutationObserverInit::new();
+        options.child_list(true);
+        self.observer
+            .observe_with_options(&target, &options)
+            .expect("valid call");
+        self.connected = true;
+    }
+
+    fn disconnect(&mut self) {
+        if self.connected {
+            self.observer.disconnect();
+            self.connected = false;
+        }
+    }
+}
@@ -0,0 +1,51 @@
+//! Self-hosted sync endpoint, built with `--features sync-server`.
+//!
+//! Stores the most recently pushed line set in a JSON file and serves it
+//! back for pulls; conflict resolution happens client-side using the
+//! per-line versions, so the server can stay a dumb store.
+
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use axum::extract::State;
+use axum::routing::get;
+use axum::Router;
+use tower_http::cors::CorsLayer;
+
+/// The synced document plus the file it is persisted to.
+#[derive(Clone)]
+struct Store {
+    path: PathBuf,
+    value: Arc<Mutex<String>>,
+}
+
+#[tokio::main]
+async fn main() {
+    let path = PathBuf::from(
+        std::env::args()
+            .nth(1)
+            .unwrap_or_else(|| "texthooker-sync.json".to_string()),
+    );
+    let value = std::fs::read_to_string(&path).unwrap_or_else(|_| "{}".to_string());
+    let store = Store {
+        path,
+        value: Arc::new(Mutex::new(value)),
+    };
+    let app = Router::new()
+        .route("/sync", get(pull).post(push))
+        .layer(CorsLayer::permissive())
+        .with_state(store);
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:8766")
+        .await
+        .expect("valid bind");
+    axum::serve(listener, app).await.expect("valid serve");
+}
+
+async fn pull(State(store): State<Store>) -> String {
+    store.value.lock().expect("valid lock").clone()
+}
+
+async fn push(State(store): State<Store>, body: String) {
+    std::fs::write(&store.path, &body).expect("valid write");
+    *store.value.lock().expect("valid lock") = body;
+}
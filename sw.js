@@ -0,0 +1,62 @@
+// Offline support. Trunk fingerprints the wasm/js/css asset names, so a
+// fixed precache list can't know them ahead of time; instead every
+// successful same-origin fetch is cached as it happens, and served
+// cache-first afterwards. After one online load the app works fully
+// offline (it is localStorage-only at runtime).
+const CACHE = "texthooker-v1";
+
+self.addEventListener("install", (event) => {
+    event.waitUntil(caches.open(CACHE).then((cache) => cache.addAll(["./"])));
+    self.skipWaiting();
+});
+
+self.addEventListener("activate", (event) => {
+    event.waitUntil(
+        caches
+            .keys()
+            .then((keys) =>
+                Promise.all(keys.filter((key) => key !== CACHE).map((key) => caches.delete(key))),
+            ),
+    );
+});
+
+self.addEventListener("fetch", (event) => {
+    const request = event.request;
+    if (request.method !== "GET" || !request.url.startsWith(self.location.origin)) {
+        return;
+    }
+    // The app's update check opts out of caching entirely.
+    if (request.cache === "no-store") {
+        return;
+    }
+    // Navigations go network-first so a reload picks up new builds; the
+    // fingerprinted assets they reference are immutable and stay
+    // cache-first below.
+    if (request.mode === "navigate") {
+        event.respondWith(
+            fetch(request)
+                .then((response) => {
+                    if (response.ok) {
+                        const copy = response.clone();
+                        caches.open(CACHE).then((cache) => cache.put(request, copy));
+                    }
+                    return response;
+                })
+                .catch(() => caches.match(request)),
+        );
+        return;
+    }
+    event.respondWith(
+        caches.match(request).then(
+            (cached) =>
+                cached ||
+                fetch(request).then((response) => {
+                    if (response.ok) {
+                        const copy = response.clone();
+                        caches.open(CACHE).then((cache) => cache.put(request, copy));
+                    }
+                    return response;
+                }),
+        ),
+    );
+});
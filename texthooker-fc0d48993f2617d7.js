@@ -1 +0,0 @@
-let Z=0,a0=`string`,V=1,a1=`Object`,X=`utf-8`,U=null,W=`undefined`,a4=4,$=`function`,a3=16,a8=34,a9=302,S=Array,Y=Error,a2=FinalizationRegistry,a6=Object,a7=Promise,a5=Reflect,_=Uint8Array,T=undefined;var M=(async(a,b)=>{if(typeof Response===$&&a instanceof Response){if(typeof WebAssembly.instantiateStreaming===$){try{return await WebAssembly.instantiateStreaming(a,b)}catch(b){if(a.headers.get(`Content-Type`)!=`application/wasm`){console.warn(`\`WebAssembly.instantiateStreaming\` failed because your server does not serve wasm with \`application/wasm\` MIME type. Falling back to \`WebAssembly.instantiate\` which is slower. Original error:\\n`,b)}else{throw b}}};const c=await a.arrayBuffer();return await WebAssembly.instantiate(c,b)}else{const c=await WebAssembly.instantiate(a,b);if(c instanceof WebAssembly.Instance){return {instance:c,module:a}}else{return c}}});var s=(a=>{const b=typeof a;if(b==`number`||b==`boolean`||a==U){return `${a}`};if(b==a0){return `"${a}"`};if(b==`symbol`){const b=a.description;if(b==U){return `Symbol`}else{return `Symbol(${b})`}};if(b==$){const b=a.name;if(typeof b==a0&&b.length>Z){return `Function(${b})`}else{return `Function`}};if(S.isArray(a)){const b=a.length;let c=`[`;if(b>Z){c+=s(a[Z])};for(let d=V;d<b;d++){c+=`, `+ s(a[d])};c+=`]`;return c};const c=/\[object ([^\]]+)\]/.exec(toString.call(a));let d;if(c.length>V){d=c[V]}else{return toString.call(a)};if(d==a1){try{return `Object(`+ JSON.stringify(a)+ `)`}catch(a){return a1}};if(a instanceof Y){return `${a.name}: ${a.message}\n${a.stack}`};return d});var O=((a,b)=>{});var R=(async(b)=>{if(a!==T)return a;if(typeof b===W){b=new URL(`texthooker-fc0d48993f2617d7_bg.wasm`,import.meta.url)};const c=N();if(typeof b===a0||typeof Request===$&&b instanceof Request||typeof URL===$&&b instanceof URL){b=fetch(b)};O(c);const {instance:d,module:e}=await M(await b,c);return P(d,e)});var v=((b,c)=>{a.wasm_bindgen__convert__closures__invoke0_mut__h6f5af532f004194a(b,c)});var g=(a=>{if(d===b.length)b.push(b.length+ V);const c=d;d=b[c];b[c]=a;return c});var f=(a=>{const b=c(a);e(a);return b});var Q=(b=>{if(a!==T)return a;const c=N();O(c);if(!(b instanceof WebAssembly.Module)){b=new WebAssembly.Module(b)};const d=new WebAssembly.Instance(b,c);return P(d,b)});var z=((b,c)=>{a.wasm_bindgen__convert__closures__invoke0_mut__haa5c9edfcfb4cbbf(b,c)});var P=((b,c)=>{a=b.exports;R.__wbindgen_wasm_module=c;q=U;i=U;a.__wbindgen_start();return a});var N=(()=>{const b={};b.wbg={};b.wbg.__wbindgen_object_drop_ref=(a=>{f(a)});b.wbg.__wbindgen_object_clone_ref=(a=>{const b=c(a);return g(b)});b.wbg.__wbindgen_cb_drop=(a=>{const b=f(a).original;if(b.cnt--==V){b.a=Z;return !0};const c=!1;return c});b.wbg.__wbindgen_string_new=((a,b)=>{const c=k(a,b);return g(c)});b.wbg.__wbindgen_number_new=(a=>{const b=a;return g(b)});b.wbg.__wbindgen_string_get=((b,d)=>{const e=c(d);const f=typeof e===a0?e:T;var g=p(f)?Z:o(f,a.__wbindgen_malloc,a.__wbindgen_realloc);var h=l;r()[b/a4+ V]=h;r()[b/a4+ Z]=g});b.wbg.__wbg_new_abda76e883ba8a5f=(()=>{const a=new Y();return g(a)});b.wbg.__wbg_stack_658279fe44541cf6=((b,d)=>{const e=c(d).stack;const f=o(e,a.__wbindgen_malloc,a.__wbindgen_realloc);const g=l;r()[b/a4+ V]=g;r()[b/a4+ Z]=f});b.wbg.__wbg_error_f851667af71bcfc6=((b,c)=>{var d=D(b,c);if(b!==Z){a.__wbindgen_free(b,c,V)};console.error(d)});b.wbg.__wbindgen_is_undefined=(a=>{const b=c(a)===T;return b});b.wbg.__wbindgen_is_null=(a=>{const b=c(a)===U;return b});b.wbg.__wbindgen_is_falsy=(a=>{const b=!c(a);return b});b.wbg.__wbg_instanceof_Window_f401953a2cf86220=(a=>{let b;try{b=c(a) instanceof Window}catch(a){b=!1}const d=b;return d});b.wbg.__wbg_document_5100775d18896c16=(a=>{const b=c(a).document;return p(b)?Z:g(b)});b.wbg.__wbg_localStorage_e381d34d0c40c761=function(){return E((a=>{const b=c(a).localStorage;return p(b)?Z:g(b)}),arguments)};b.wbg.__wbg_sessionStorage_0a3bf8c7b2253d1a=function(){return E((a=>{const b=c(a).sessionStorage;return p(b)?Z:g(b)}),arguments)};b.wbg.__wbg_getSelection_1ba596ac4cddb0f1=function(){return E((a=>{const b=c(a).getSelection();return p(b)?Z:g(b)}),arguments)};b.wbg.__wbg_scrollTo_4d970c5e1c4b340b=((a,b,d)=>{c(a).scrollTo(b,d)});b.wbg.__wbg_clearTimeout_ba63ae54a36e111e=((a,b)=>{c(a).clearTimeout(b)});b.wbg.__wbg_setTimeout_c172d5704ef82276=function(){return E(((a,b,d)=>{const e=c(a).setTimeout(c(b),d);return e}),arguments)};b.wbg.__wbg_documentElement_da9c841ddb352d95=(a=>{const b=c(a).documentElement;return p(b)?Z:g(b)});b.wbg.__wbg_body_edb1908d3ceff3a1=(a=>{const b=c(a).body;return p(b)?Z:g(b)});b.wbg.__wbg_activeElement_fa7feca08f5028c0=(a=>{const b=c(a).activeElement;return p(b)?Z:g(b)});b.wbg.__wbg_createComment_354ccab4fdc521ee=((a,b,d)=>{var e=D(b,d);const f=c(a).createComment(e);return g(f)});b.wbg.__wbg_createDocumentFragment_8c86903bbb0a3c3c=(a=>{const b=c(a).createDocumentFragment();return g(b)});b.wbg.__wbg_createElement_8bae7856a4bb7411=function(){return E(((a,b,d)=>{var e=D(b,d);const f=c(a).createElement(e);return g(f)}),arguments)};b.wbg.__wbg_createTextNode_0c38fd80a5b2284d=((a,b,d)=>{var e=D(b,d);const f=c(a).createTextNode(e);return g(f)});b.wbg.__wbg_getElementById_c369ff43f0db99cf=((a,b,d)=>{var e=D(b,d);const f=c(a).getElementById(e);return p(f)?Z:g(f)});b.wbg.__wbg_classList_1f0528ee002e56d4=(a=>{const b=c(a).classList;return g(b)});b.wbg.__wbg_setinnerHTML_26d69b59e1af99c7=((a,b,d)=>{var e=D(b,d);c(a).innerHTML=e});b.wbg.__wbg_children_8904f20261f6442c=(a=>{const b=c(a).children;return g(b)});b.wbg.__wbg_removeAttribute_1b10a06ae98ebbd1=function(){return E(((a,b,d)=>{var e=D(b,d);c(a).removeAttribute(e)}),arguments)};b.wbg.__wbg_setAttribute_3c9f6c303b696daa=function(){return E(((a,b,d,e,f)=>{var g=D(b,d);var h=D(e,f);c(a).setAttribute(g,h)}),arguments)};b.wbg.__wbg_before_210596e44d88649f=function(){return E(((a,b)=>{c(a).before(c(b))}),arguments)};b.wbg.__wbg_remove_49b0a5925a04b955=(a=>{c(a).remove()});b.wbg.__wbg_append_fcf463f0b4a8f219=function(){return E(((a,b)=>{c(a).append(c(b))}),arguments)};b.wbg.__wbg_altKey_2e6c34c37088d8b1=(a=>{const b=c(a).altKey;return b});b.wbg.__wbg_ctrlKey_bb5b6fef87339703=(a=>{const b=c(a).ctrlKey;return b});b.wbg.__wbg_shiftKey_5911baf439ab232b=(a=>{const b=c(a).shiftKey;return b});b.wbg.__wbg_code_3b0c3912a2351163=((b,d)=>{const e=c(d).code;const f=o(e,a.__wbindgen_malloc,a.__wbindgen_realloc);const g=l;r()[b/a4+ V]=g;r()[b/a4+ Z]=f});b.wbg.__wbg_new_f05b7ebcc7f0ed87=function(){return E((a=>{const b=new MutationObserver(c(a));return g(b)}),arguments)};b.wbg.__wbg_observe_ee374fbdad58b84f=function(){return E(((a,b,d)=>{c(a).observe(c(b),c(d))}),arguments)};b.wbg.__wbg_setdata_8c2b43af041cc1b3=((a,b,d)=>{var e=D(b,d);c(a).data=e});b.wbg.__wbg_instanceof_ShadowRoot_9db040264422e84a=(a=>{let b;try{b=c(a) instanceof ShadowRoot}catch(a){b=!1}const d=b;return d});b.wbg.__wbg_host_c667c7623404d6bf=(a=>{const b=c(a).host;return g(b)});b.wbg.__wbg_append_7ba9d5c2eb183eea=function(){return E(((a,b)=>{c(a).append(c(b))}),arguments)};b.wbg.__wbg_view_7f0ce470793a340f=(a=>{const b=c(a).view;return p(b)?Z:g(b)});b.wbg.__wbg_respond_b1a43b2e3a06d525=function(){return E(((a,b)=>{c(a).respond(b>>>Z)}),arguments)};b.wbg.__wbg_scrollHeight_2d7f990052968501=(a=>{const b=c(a).scrollHeight;return b});b.wbg.__wbg_innerText_f202d7ac8d827e5a=((b,d)=>{const e=c(d).innerText;const f=o(e,a.__wbindgen_malloc,a.__wbindgen_realloc);const g=l;r()[b/a4+ V]=g;r()[b/a4+ Z]=f});b.wbg.__wbg_setcontentEditable_93193ea358a10e7d=((a,b,d)=>{var e=D(b,d);c(a).contentEditable=e});b.wbg.__wbg_blur_51f7b635f18a0eec=function(){return E((a=>{c(a).blur()}),arguments)};b.wbg.__wbg_click_897b305b2e10b9cf=(a=>{c(a).click()});b.wbg.__wbg_focus_39d4b8ba8ff9df14=function(){return E((a=>{c(a).focus()}),arguments)};b.wbg.__wbg_new_c7aa03c061e95bde=function(){return E((()=>{const a=new Range();return g(a)}),arguments)};b.wbg.__wbg_deleteContents_1b5a33e17bc6400f=function(){return E((a=>{c(a).deleteContents()}),arguments)};b.wbg.__wbg_setEndBefore_6d219390ff50f205=function(){return E(((a,b)=>{c(a).setEndBefore(c(b))}),arguments)};b.wbg.__wbg_setStartBefore_2dac025de1f18aa0=function(){return E(((a,b)=>{c(a).setStartBefore(c(b))}),arguments)};b.wbg.__wbg_addEventListener_53b787075bd5e003=function(){return E(((a,b,d,e)=>{var f=D(b,d);c(a).addEventListener(f,c(e))}),arguments)};b.wbg.__wbg_addEventListener_4283b15b4f039eb5=function(){return E(((a,b,d,e,f)=>{var g=D(b,d);c(a).addEventListener(g,c(e),c(f))}),arguments)};b.wbg.__wbg_dispatchEvent_63c0c01600a98fd2=function(){return E(((a,b)=>{const d=c(a).dispatchEvent(c(b));return d}),arguments)};b.wbg.__wbg_removeEventListener_f3689e55cc5b09c4=function(){return E(((a,b,d,e,f)=>{var g=D(b,d);c(a).removeEventListener(g,c(e),c(f))}),arguments)};b.wbg.__wbg_type_ce0ef00e60659967=((b,d)=>{const e=c(d).type;const f=o(e,a.__wbindgen_malloc,a.__wbindgen_realloc);const g=l;r()[b/a4+ V]=g;r()[b/a4+ Z]=f});b.wbg.__wbg_target_2575c110e58d6cfa=(a=>{const b=c(a).target;return p(b)?Z:g(b)});b.wbg.__wbg_addedNodes_cfa62bdc9f836264=(a=>{const b=c(a).addedNodes;return g(b)});b.wbg.__wbg_error_8e3928cfb8a43e2b=(a=>{console.error(c(a))});b.wbg.__wbg_warn_63bbae1730aead09=(a=>{console.warn(c(a))});b.wbg.__wbg_length_d0a802565d17eec4=(a=>{const b=c(a).length;return b});b.wbg.__wbg_get_8cd5eba00ab6304f=((a,b)=>{const d=c(a)[b>>>Z];return p(d)?Z:g(d)});b.wbg.__wbg_type_f89f9daaed4ddba2=((b,d)=>{const e=c(d).type;const f=o(e,a.__wbindgen_malloc,a.__wbindgen_realloc);const g=l;r()[b/a4+ V]=g;r()[b/a4+ Z]=f});b.wbg.__wbg_containsNode_1994df676c2f5159=function(){return E(((a,b,d)=>{const e=c(a).containsNode(c(b),d!==Z);return e}),arguments)};b.wbg.__wbg_detail_40fcf009a8ab1ab9=(a=>{const b=c(a).detail;return g(b)});b.wbg.__wbg_newwitheventinitdict_df944b5b582e7ecf=function(){return E(((a,b,d)=>{var e=D(a,b);const f=new CustomEvent(e,c(d));return g(f)}),arguments)};b.wbg.__wbg_byobRequest_72fca99f9c32c193=(a=>{const b=c(a).byobRequest;return p(b)?Z:g(b)});b.wbg.__wbg_close_184931724d961ccc=function(){return E((a=>{c(a).close()}),arguments)};b.wbg.__wbg_close_a994f9425dab445c=function(){return E((a=>{c(a).close()}),arguments)};b.wbg.__wbg_enqueue_ea194723156c0cc2=function(){return E(((a,b)=>{c(a).enqueue(c(b))}),arguments)};b.wbg.__wbg_add_dcb05a8ba423bdac=function(){return E(((a,b,d)=>{var e=D(b,d);c(a).add(e)}),arguments)};b.wbg.__wbg_remove_698118fb25ab8150=function(){return E(((a,b,d)=>{var e=D(b,d);c(a).remove(e)}),arguments)};b.wbg.__wbg_setdownload_65ac7e7c800d764e=((a,b,d)=>{var e=D(b,d);c(a).download=e});b.wbg.__wbg_sethref_b0712139dd35e2fd=((a,b,d)=>{var e=D(b,d);c(a).href=e});b.wbg.__wbg_value_47fe6384562f52ab=((b,d)=>{const e=c(d).value;const f=o(e,a.__wbindgen_malloc,a.__wbindgen_realloc);const g=l;r()[b/a4+ V]=g;r()[b/a4+ Z]=f});b.wbg.__wbg_getItem_164e8e5265095b87=function(){return E(((b,d,e,f)=>{var g=D(e,f);const h=c(d).getItem(g);var i=p(h)?Z:o(h,a.__wbindgen_malloc,a.__wbindgen_realloc);var j=l;r()[b/a4+ V]=j;r()[b/a4+ Z]=i}),arguments)};b.wbg.__wbg_setItem_ba2bb41d73dac079=function(){return E(((a,b,d,e,f)=>{var g=D(b,d);var h=D(e,f);c(a).setItem(g,h)}),arguments)};b.wbg.__wbg_key_dc5b86010fc4d525=((b,d)=>{const e=c(d).key;var f=p(e)?Z:o(e,a.__wbindgen_malloc,a.__wbindgen_realloc);var g=l;r()[b/a4+ V]=g;r()[b/a4+ Z]=f});b.wbg.__wbg_newwithstrsequence_07a6a2a4a6332386=function(){return E((a=>{const b=new Blob(c(a));return g(b)}),arguments)};b.wbg.__wbg_target_2fc177e386c8b7b0=(a=>{const b=c(a).target;return p(b)?Z:g(b)});b.wbg.__wbg_cancelBubble_c0aa3172524eb03c=(a=>{const b=c(a).cancelBubble;return b});b.wbg.__wbg_composedPath_58473fd5ae55f2cd=(a=>{const b=c(a).composedPath();return g(b)});b.wbg.__wbg_relatedTarget_29cdd27e25ed3b05=(a=>{const b=c(a).relatedTarget;return p(b)?Z:g(b)});b.wbg.__wbg_length_c2d5de977172fa1a=(a=>{const b=c(a).length;return b});b.wbg.__wbg_getwithindex_6f132a79d56e81ec=((a,b)=>{const d=c(a)[b>>>Z];return p(d)?Z:g(d)});b.wbg.__wbg_nodeName_149f7e29f80d1338=((b,d)=>{const e=c(d).nodeName;const f=o(e,a.__wbindgen_malloc,a.__wbindgen_realloc);const g=l;r()[b/a4+ V]=g;r()[b/a4+ Z]=f});b.wbg.__wbg_parentNode_6be3abff20e1a5fb=(a=>{const b=c(a).parentNode;return p(b)?Z:g(b)});b.wbg.__wbg_childNodes_118168e8b23bcb9b=(a=>{const b=c(a).childNodes;return g(b)});b.wbg.__wbg_previousSibling_9708a091a3e6e03b=(a=>{const b=c(a).previousSibling;return p(b)?Z:g(b)});b.wbg.__wbg_nextSibling_709614fdb0fb7a66=(a=>{const b=c(a).nextSibling;return p(b)?Z:g(b)});b.wbg.__wbg_textContent_8e392d624539e731=((b,d)=>{const e=c(d).textContent;var f=p(e)?Z:o(e,a.__wbindgen_malloc,a.__wbindgen_realloc);var g=l;r()[b/a4+ V]=g;r()[b/a4+ Z]=f});b.wbg.__wbg_settextContent_d271bab459cbb1ba=((a,b,d)=>{var e=D(b,d);c(a).textContent=e});b.wbg.__wbg_appendChild_580ccb11a660db68=function(){return E(((a,b)=>{const d=c(a).appendChild(c(b));return g(d)}),arguments)};b.wbg.__wbg_cloneNode_e19c313ea20d5d1d=function(){return E((a=>{const b=c(a).cloneNode();return g(b)}),arguments)};b.wbg.__wbg_createObjectURL_ad8244759309f204=function(){return E(((b,d)=>{const e=URL.createObjectURL(c(d));const f=o(e,a.__wbindgen_malloc,a.__wbindgen_realloc);const g=l;r()[b/a4+ V]=g;r()[b/a4+ Z]=f}),arguments)};b.wbg.__wbg_queueMicrotask_481971b0d87f3dd4=(a=>{queueMicrotask(c(a))});b.wbg.__wbg_queueMicrotask_3cbae2ec6b6cd3d6=(a=>{const b=c(a).queueMicrotask;return g(b)});b.wbg.__wbindgen_is_function=(a=>{const b=typeof c(a)===$;return b});b.wbg.__wbg_get_bd8e338fbd5f5cc8=((a,b)=>{const d=c(a)[b>>>Z];return g(d)});b.wbg.__wbg_length_cd7af8117672b8b8=(a=>{const b=c(a).length;return b});b.wbg.__wbg_new_16b304a2cfa7ff4a=(()=>{const a=new S();return g(a)});b.wbg.__wbg_newnoargs_e258087cd0daa0ea=((a,b)=>{var c=D(a,b);const d=new Function(c);return g(d)});b.wbg.__wbg_get_e3c254076557e348=function(){return E(((a,b)=>{const d=a5.get(c(a),c(b));return g(d)}),arguments)};b.wbg.__wbg_call_27c0f87801dedf93=function(){return E(((a,b)=>{const d=c(a).call(c(b));return g(d)}),arguments)};b.wbg.__wbg_new_72fb9a18b5ae2624=(()=>{const a=new a6();return g(a)});b.wbg.__wbg_self_ce0dbfc45cf2f5be=function(){return E((()=>{const a=self.self;return g(a)}),arguments)};b.wbg.__wbg_window_c6fb939a7f436783=function(){return E((()=>{const a=window.window;return g(a)}),arguments)};b.wbg.__wbg_globalThis_d1e6af4856ba331b=function(){return E((()=>{const a=globalThis.globalThis;return g(a)}),arguments)};b.wbg.__wbg_global_207b558942527489=function(){return E((()=>{const a=global.global;return g(a)}),arguments)};b.wbg.__wbg_push_a5b05aedc7234f9f=((a,b)=>{const d=c(a).push(c(b));return d});b.wbg.__wbg_new_28c511d9baebfa89=((a,b)=>{var c=D(a,b);const d=new Y(c);return g(d)});b.wbg.__wbg_call_b3ca7c6051f9bec1=function(){return E(((a,b,d)=>{const e=c(a).call(c(b),c(d));return g(e)}),arguments)};b.wbg.__wbg_now_3014639a94423537=(()=>{const a=Date.now();return a});b.wbg.__wbg_is_010fdc0f4ab96916=((a,b)=>{const d=a6.is(c(a),c(b));return d});b.wbg.__wbg_toString_c816a20ab859d0c1=(a=>{const b=c(a).toString();return g(b)});b.wbg.__wbg_new_81740750da40724f=((a,b)=>{try{var c={a:a,b:b};var d=(a,b)=>{const d=c.a;c.a=Z;try{return F(d,c.b,a,b)}finally{c.a=d}};const e=new a7(d);return g(e)}finally{c.a=c.b=Z}});b.wbg.__wbg_resolve_b0083a7967828ec8=(a=>{const b=a7.resolve(c(a));return g(b)});b.wbg.__wbg_then_0c86a60e8fcfe9f6=((a,b)=>{const d=c(a).then(c(b));return g(d)});b.wbg.__wbg_buffer_12d079cc21e14bdb=(a=>{const b=c(a).buffer;return g(b)});b.wbg.__wbg_newwithbyteoffsetandlength_aa4a17c33a06e5cb=((a,b,d)=>{const e=new _(c(a),b>>>Z,d>>>Z);return g(e)});b.wbg.__wbg_set_a47bac70306a19a7=((a,b,d)=>{c(a).set(c(b),d>>>Z)});b.wbg.__wbg_length_c20a40f15020d68a=(a=>{const b=c(a).length;return b});b.wbg.__wbg_buffer_dd7f74bc60f1faab=(a=>{const b=c(a).buffer;return g(b)});b.wbg.__wbg_byteLength_58f7b4fab1919d44=(a=>{const b=c(a).byteLength;return b});b.wbg.__wbg_byteOffset_81d60f7392524f62=(a=>{const b=c(a).byteOffset;return b});b.wbg.__wbg_set_1f9b04f170055d33=function(){return E(((a,b,d)=>{const e=a5.set(c(a),c(b),c(d));return e}),arguments)};b.wbg.__wbindgen_debug_string=((b,d)=>{const e=s(c(d));const f=o(e,a.__wbindgen_malloc,a.__wbindgen_realloc);const g=l;r()[b/a4+ V]=g;r()[b/a4+ Z]=f});b.wbg.__wbindgen_throw=((a,b)=>{throw new Y(k(a,b))});b.wbg.__wbindgen_memory=(()=>{const b=a.memory;return g(b)});b.wbg.__wbindgen_closure_wrapper290=((a,b,c)=>{const d=u(a,b,a8,v);return g(d)});b.wbg.__wbindgen_closure_wrapper292=((a,b,c)=>{const d=w(a,b,a8,x);return g(d)});b.wbg.__wbindgen_closure_wrapper293=((a,b,c)=>{const d=u(a,b,a8,y);return g(d)});b.wbg.__wbindgen_closure_wrapper295=((a,b,c)=>{const d=u(a,b,a8,y);return g(d)});b.wbg.__wbindgen_closure_wrapper297=((a,b,c)=>{const d=u(a,b,a8,y);return g(d)});b.wbg.__wbindgen_closure_wrapper299=((a,b,c)=>{const d=u(a,b,a8,y);return g(d)});b.wbg.__wbindgen_closure_wrapper302=((a,b,c)=>{const d=u(a,b,a8,y);return g(d)});b.wbg.__wbindgen_closure_wrapper304=((a,b,c)=>{const d=u(a,b,a8,y);return g(d)});b.wbg.__wbindgen_closure_wrapper786=((a,b,c)=>{const d=u(a,b,a9,z);return g(d)});b.wbg.__wbindgen_closure_wrapper787=((a,b,c)=>{const d=u(a,b,a9,A);return g(d)});b.wbg.__wbindgen_closure_wrapper912=((a,b,c)=>{const d=u(a,b,349,B);return g(d)});b.wbg.__wbindgen_closure_wrapper2704=((a,b,c)=>{const d=u(a,b,382,C);return g(d)});return b});function E(b,c){try{return b.apply(this,c)}catch(b){a.__wbindgen_exn_store(g(b))}}var D=((a,b)=>{if(a===Z){return c(b)}else{return k(a,b)}});var C=((b,c,d)=>{a._dyn_core__ops__function__FnMut__A____Output___R_as_wasm_bindgen__closure__WasmClosure___describe__invoke__h64a590cdffbf3be6(b,c,g(d))});var r=(()=>{if(q===U||q.byteLength===Z){q=new Int32Array(a.memory.buffer)};return q});var p=(a=>a===T||a===U);var c=(a=>b[a]);var B=((b,c,d)=>{a.wasm_bindgen__convert__closures__invoke1_mut__h0517559217906945(b,c,g(d))});var x=((b,c,d,e)=>{a.wasm_bindgen__convert__closures__invoke2__h54b3a285acb2f21e(b,c,g(d),g(e))});var F=((b,c,d,e)=>{a.wasm_bindgen__convert__closures__invoke2_mut__h3eb1e45b460b5cd2(b,c,g(d),g(e))});var A=((b,c,d)=>{a.wasm_bindgen__convert__closures__invoke1_mut__h014c02da683f5964(b,c,g(d))});var w=((b,c,d,e)=>{const f={a:b,b:c,cnt:V,dtor:d};const g=(...b)=>{f.cnt++;try{return e(f.a,f.b,...b)}finally{if(--f.cnt===Z){a.__wbindgen_export_2.get(f.dtor)(f.a,f.b);f.a=Z;t.unregister(f)}}};g.original=f;t.register(g,f,f);return g});var e=(a=>{if(a<132)return;b[a]=d;d=a});var u=((b,c,d,e)=>{const f={a:b,b:c,cnt:V,dtor:d};const g=(...b)=>{f.cnt++;const c=f.a;f.a=Z;try{return e(c,f.b,...b)}finally{if(--f.cnt===Z){a.__wbindgen_export_2.get(f.dtor)(c,f.b);t.unregister(f)}else{f.a=c}}};g.original=f;t.register(g,f,f);return g});var o=((a,b,c)=>{if(c===T){const c=m.encode(a);const d=b(c.length,V)>>>Z;j().subarray(d,d+ c.length).set(c);l=c.length;return d};let d=a.length;let e=b(d,V)>>>Z;const f=j();let g=Z;for(;g<d;g++){const b=a.charCodeAt(g);if(b>127)break;f[e+ g]=b};if(g!==d){if(g!==Z){a=a.slice(g)};e=c(e,d,d=g+ a.length*3,V)>>>Z;const b=j().subarray(e+ g,e+ d);const f=n(a,b);g+=f.written;e=c(e,d,g,V)>>>Z};l=g;return e});var j=(()=>{if(i===U||i.byteLength===Z){i=new _(a.memory.buffer)};return i});var k=((a,b)=>{a=a>>>Z;return h.decode(j().subarray(a,a+ b))});var y=((b,c,d)=>{a.wasm_bindgen__convert__closures__invoke1_mut__h3f1805c3c97470f6(b,c,g(d))});let a;const b=new S(128).fill(T);b.push(T,U,!0,!1);let d=b.length;const h=typeof TextDecoder!==W?new TextDecoder(X,{ignoreBOM:!0,fatal:!0}):{decode:()=>{throw Y(`TextDecoder not available`)}};if(typeof TextDecoder!==W){h.decode()};let i=U;let l=Z;const m=typeof TextEncoder!==W?new TextEncoder(X):{encode:()=>{throw Y(`TextEncoder not available`)}};const n=typeof m.encodeInto===$?((a,b)=>m.encodeInto(a,b)):((a,b)=>{const c=m.encode(a);b.set(c);return {read:a.length,written:c.length}});let q=U;const t=typeof a2===W?{register:()=>{},unregister:()=>{}}:new a2(b=>{a.__wbindgen_export_2.get(b.dtor)(b.a,b.b)});const G=typeof a2===W?{register:()=>{},unregister:()=>{}}:new a2(b=>a.__wbg_intounderlyingbytesource_free(b>>>Z));class H{__destroy_into_raw(){const a=this.__wbg_ptr;this.__wbg_ptr=Z;G.unregister(this);return a}free(){const b=this.__destroy_into_raw();a.__wbg_intounderlyingbytesource_free(b)}type(){try{const e=a.__wbindgen_add_to_stack_pointer(-a3);a.intounderlyingbytesource_type(e,this.__wbg_ptr);var b=r()[e/a4+ Z];var c=r()[e/a4+ V];var d=D(b,c);if(b!==Z){a.__wbindgen_free(b,c,V)};return d}finally{a.__wbindgen_add_to_stack_pointer(a3)}}autoAllocateChunkSize(){const b=a.intounderlyingbytesource_autoAllocateChunkSize(this.__wbg_ptr);return b>>>Z}start(b){a.intounderlyingbytesource_start(this.__wbg_ptr,g(b))}pull(b){const c=a.intounderlyingbytesource_pull(this.__wbg_ptr,g(b));return f(c)}cancel(){const b=this.__destroy_into_raw();a.intounderlyingbytesource_cancel(b)}}const I=typeof a2===W?{register:()=>{},unregister:()=>{}}:new a2(b=>a.__wbg_intounderlyingsink_free(b>>>Z));class J{__destroy_into_raw(){const a=this.__wbg_ptr;this.__wbg_ptr=Z;I.unregister(this);return a}free(){const b=this.__destroy_into_raw();a.__wbg_intounderlyingsink_free(b)}write(b){const c=a.intounderlyingsink_write(this.__wbg_ptr,g(b));return f(c)}close(){const b=this.__destroy_into_raw();const c=a.intounderlyingsink_close(b);return f(c)}abort(b){const c=this.__destroy_into_raw();const d=a.intounderlyingsink_abort(c,g(b));return f(d)}}const K=typeof a2===W?{register:()=>{},unregister:()=>{}}:new a2(b=>a.__wbg_intounderlyingsource_free(b>>>Z));class L{__destroy_into_raw(){const a=this.__wbg_ptr;this.__wbg_ptr=Z;K.unregister(this);return a}free(){const b=this.__destroy_into_raw();a.__wbg_intounderlyingsource_free(b)}pull(b){const c=a.intounderlyingsource_pull(this.__wbg_ptr,g(b));return f(c)}cancel(){const b=this.__destroy_into_raw();a.intounderlyingsource_cancel(b)}}export default R;export{H as IntoUnderlyingByteSource,J as IntoUnderlyingSink,L as IntoUnderlyingSource,Q as initSync}
\ No newline at end of file